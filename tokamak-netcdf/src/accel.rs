@@ -0,0 +1,119 @@
+//! Cached cell lookups for successive evaluations.
+
+/// Cache of the ψ and θ grid cells of the last evaluation, which speeds up finding the next
+/// evaluation's cells, like GSL's `gsl_interp_accel`.
+///
+/// Successive evaluations along an orbit land in the same or neighbouring cells. Each search
+/// starts from the cached cell and hunts outwards with growing steps, before bisecting the
+/// bracket it found, so that it costs O(1) for neighbouring cells instead of the O(log n) of a
+/// bisection of the whole grid. Far jumps cost at most twice a bisection.
+///
+/// An `Accelerator` only affects the speed of the evaluations, never their results. Use one per
+/// orbit; it can be shared by the evaluators of the same equilibrium.
+///
+/// ```
+/// # use tokamak_netcdf::{Accelerator, CircularTokamak, NcError};
+/// # fn main() -> Result<(), NcError> {
+/// let nc_data = CircularTokamak::default().to_nc_data()?;
+/// let bfield = nc_data.bfield_spline()?;
+/// let mut acc = Accelerator::new();
+/// for step in 0..1000 {
+///     let (psi, theta) = (0.02 + 1e-5 * step as f64, 0.01 * step as f64);
+///     let (i, g, _, _) = nc_data.currents.eval_with(psi, &mut acc)?;
+///     let (b, _, _, _) = bfield.eval_with(psi, theta, &mut acc)?;
+///     assert_eq!(b, bfield.eval(psi, theta)?.0);
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Accelerator {
+    /// Last ψ cell.
+    pub(crate) psi: usize,
+    /// Last θ cell.
+    pub(crate) theta: usize,
+}
+
+impl Accelerator {
+    /// Creates an `Accelerator` starting from the first cells.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Forgets the cached cells, e.g. before following another orbit.
+    pub fn reset(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Returns the index of the cell of `nodes` that evaluates `x`: the interval containing it, or
+/// the closest one outside the nodes, hunting from the cell `guess`. Needs at least 2 nodes.
+pub(crate) fn hunt(nodes: &[f64], x: f64, guess: usize) -> usize {
+    let last = nodes.len() - 2;
+    let guess = guess.min(last);
+    // Bisects the cells in [lo, hi), knowing that `nodes[lo] <= x`.
+    let bisect = |lo: usize, hi: usize| lo + nodes[lo + 1..hi].partition_point(|&node| node <= x);
+
+    let mut step = 1;
+    match nodes[guess] <= x {
+        true => {
+            let mut lo = guess;
+            loop {
+                let hi = lo + step;
+                if hi > last {
+                    return bisect(lo, last + 1);
+                }
+                if nodes[hi] > x {
+                    return bisect(lo, hi);
+                }
+                lo = hi;
+                step *= 2;
+            }
+        }
+        false => {
+            let mut hi = guess;
+            loop {
+                if hi == 0 {
+                    return 0;
+                }
+                let lo = hi.saturating_sub(step);
+                if nodes[lo] <= x {
+                    return bisect(lo, hi);
+                }
+                hi = lo;
+                step *= 2;
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_hunt() {
+        let nodes: Vec<f64> = (0..50).map(|k| (k as f64).powf(1.5)).collect();
+        let bisection = |x: f64| {
+            nodes
+                .partition_point(|&node| node <= x)
+                .saturating_sub(1)
+                .min(nodes.len() - 2)
+        };
+        let points = [-10.0, 0.0, 0.5, 1.0, 17.3, 343.0, 344.0, 400.0, f64::NAN];
+        for guess in [0, 1, 7, 25, 47, 48, 100] {
+            for x in points {
+                assert_eq!(
+                    hunt(&nodes, x, guess),
+                    bisection(x),
+                    "x = {x}, guess = {guess}"
+                );
+            }
+        }
+        // Nodes themselves belong to the cell they start, except the last one.
+        for (k, &node) in nodes.iter().enumerate() {
+            assert_eq!(hunt(&nodes, node, 20), k.min(48));
+        }
+        assert_eq!(hunt(&[0.0, 1.0], 5.0, 3), 0);
+    }
+}
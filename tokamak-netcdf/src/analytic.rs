@@ -0,0 +1,163 @@
+//! Analytic equilibria, for use as test fixtures and benchmarks.
+
+use std::f64::consts::TAU;
+#[cfg(feature = "netcdf")]
+use std::path::Path;
+use std::path::PathBuf;
+
+use ndarray::{Array1, Array2};
+
+use crate::raw::RawEquilibrium;
+use crate::{NcData, NcError, NcSchema, Result};
+
+/// Large aspect ratio tokamak with circular, concentric flux surfaces.
+///
+/// To first order in the inverse aspect ratio ε = r/R0, and in the crate's normalised units,
+///
+/// - ψ = ε²/2,
+/// - q(ψ) = q0 + (qa - q0) ψ/ψa, which is parabolic in the minor radius,
+/// - ψp = ∫ dψ/q,
+/// - B = 1 - ε cos θ,
+/// - g = 1 and I = ε²/q,
+/// - R = 1 + ε cos θ and Z = ε sin θ, in units of R0.
+///
+/// ```
+/// # use tokamak_netcdf::{CircularTokamak, NcError};
+/// # fn main() -> Result<(), NcError> {
+/// let nc_data = CircularTokamak {
+///     q0: 1.0,
+///     qa: 4.0,
+///     ..Default::default()
+/// }
+/// .to_nc_data()?;
+/// assert!(nc_data.validate().is_valid());
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct CircularTokamak {
+    /// Magnetic field strength on the axis in \[*T*\].
+    pub baxis: f64,
+    /// Major radius in \[*m*\].
+    pub raxis: f64,
+    /// Minor radius of the last surface in \[*m*\].
+    pub minor_radius: f64,
+    /// Safety factor on the axis.
+    pub q0: f64,
+    /// Safety factor on the last surface.
+    pub qa: f64,
+    /// Number of flux surfaces, excluding the axis.
+    pub psi_len: usize,
+    /// Number of θ points. The grid is uniform in \[0, 2π) and does not include 2π.
+    pub theta_len: usize,
+}
+
+impl Default for CircularTokamak {
+    fn default() -> Self {
+        Self {
+            baxis: 2.0,
+            raxis: 1.65,
+            minor_radius: 0.5,
+            q0: 1.1,
+            qa: 3.5,
+            psi_len: 100,
+            theta_len: 64,
+        }
+    }
+}
+
+impl CircularTokamak {
+    /// Creates an `NcData` equivalent to opening a NetCDF file with the default [`NcSchema`].
+    /// The returned `NcData`'s path is empty.
+    pub fn to_nc_data(&self) -> Result<NcData> {
+        self.to_raw()?
+            .into_nc_data(PathBuf::new(), &NcSchema::default())
+    }
+
+    /// Writes the equilibrium to a NetCDF file, in the layout [`NcData::open`] reads.
+    #[cfg(feature = "netcdf")]
+    pub fn write(&self, path: &Path) -> Result<()> {
+        self.to_nc_data()?.write(path)
+    }
+
+    /// Safety factor as a function of ψ.
+    fn q(&self, psi: f64, psi_wall: f64) -> f64 {
+        self.q0 + (self.qa - self.q0) * psi / psi_wall
+    }
+
+    /// Poloidal flux, the integral of 1/q from the axis.
+    fn psip(&self, psi: f64, psi_wall: f64) -> f64 {
+        let slope = (self.qa - self.q0) / psi_wall;
+        match slope.abs() < f64::EPSILON {
+            true => psi / self.q0,
+            false => (self.q(psi, psi_wall) / self.q0).ln() / slope,
+        }
+    }
+
+    fn to_raw(&self) -> Result<RawEquilibrium> {
+        if self.psi_len == 0 || self.theta_len < 2 {
+            return Err(NcError::InvalidGrid(
+                "psi_len must be positive and theta_len at least 2".into(),
+            ));
+        }
+        let epsilon_wall = self.minor_radius / self.raxis;
+        if !(epsilon_wall > 0.0 && epsilon_wall < 1.0) {
+            return Err(NcError::InvalidGrid(
+                "minor radius must be positive and smaller than the major radius".into(),
+            ));
+        }
+        if !(self.q0 > 0.0 && self.qa > 0.0) {
+            return Err(NcError::InvalidGrid("q must be positive".into()));
+        }
+
+        let psi_wall = epsilon_wall.powi(2) / 2.0;
+        let psi = Array1::from_shape_fn(self.psi_len, |k| {
+            psi_wall * (k + 1) as f64 / self.psi_len as f64
+        });
+        let theta =
+            Array1::from_shape_fn(self.theta_len, |j| TAU * j as f64 / self.theta_len as f64);
+        let epsilon = psi.mapv(|psi| (2.0 * psi).sqrt());
+        let q = psi.mapv(|psi| self.q(psi, psi_wall));
+
+        let grid = (self.psi_len, self.theta_len);
+        let b = Array2::from_shape_fn(grid, |(k, j)| 1.0 - epsilon[k] * theta[j].cos());
+        let r = Array2::from_shape_fn(grid, |(k, j)| 1.0 + epsilon[k] * theta[j].cos());
+        let z = Array2::from_shape_fn(grid, |(k, j)| epsilon[k] * theta[j].sin());
+
+        Ok(RawEquilibrium {
+            baxis: self.baxis,
+            raxis: self.raxis,
+            i: &epsilon * &epsilon / &q,
+            g: Array1::ones(self.psi_len),
+            psip: Some(psi.mapv(|psi| self.psip(psi, psi_wall))),
+            q: Some(q),
+            psi,
+            theta,
+            b,
+            rz: Some((r, z)),
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_psip() {
+        let tokamak = CircularTokamak::default();
+        let psi_wall = 0.05;
+
+        // dψp/dψ = 1/q, checked with a central difference.
+        let (psi, h) = (0.02, 1e-6);
+        let derivative =
+            (tokamak.psip(psi + h, psi_wall) - tokamak.psip(psi - h, psi_wall)) / (2.0 * h);
+        assert!((derivative - 1.0 / tokamak.q(psi, psi_wall)).abs() < 1e-8);
+
+        let flat = CircularTokamak {
+            qa: 1.1,
+            ..Default::default()
+        };
+        assert!((flat.psip(psi, psi_wall) - psi / 1.1).abs() < 1e-15);
+    }
+}
@@ -0,0 +1,267 @@
+//! Extrapolation of the equilibrium's quantities to the magnetic axis.
+
+use ndarray::{Array1, Array2, ArrayView1, Axis, concatenate};
+
+use crate::{NcError, Result};
+
+/// Strategy for extrapolating a quantity to the magnetic axis (ψ = 0), where reconstructed
+/// equilibria usually have no data.
+///
+/// For quantities defined on the (ψ, θ) grid, each θ column is extrapolated separately and the
+/// results are averaged, so that the quantity is single-valued on the axis. Files whose ψ grid
+/// already starts at 0 have their own axis values, and none are added.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum AxisExtrapolation {
+    /// No axis value is added.
+    None,
+    /// Prepends a fixed value.
+    Constant(f64),
+    /// Repeats the value on the surface closest to the axis, which results in a zero gradient.
+    Nearest,
+    /// Linear in ψ, through the 2 surfaces closest to the axis.
+    Linear,
+    /// Quadratic in ψ, through the 3 surfaces closest to the axis.
+    Quadratic,
+    /// Polynomial of the given degree in √ψ, through the `degree + 1` surfaces closest to the
+    /// axis. Suited for quantities that scale with the minor radius, such as R, Z and |B|.
+    SqrtPolynomial(usize),
+}
+
+impl AxisExtrapolation {
+    /// Returns the number of surfaces the extrapolation uses.
+    fn points_needed(&self) -> usize {
+        use AxisExtrapolation::*;
+        match self {
+            None | Constant(_) => 0,
+            Nearest => 1,
+            Linear => 2,
+            Quadratic => 3,
+            SqrtPolynomial(degree) => degree + 1,
+        }
+    }
+
+    /// Calculates the value on the axis from the values on the ψ grid, or `None` if no axis
+    /// value should be added.
+    fn axis_value(
+        &self,
+        name: &str,
+        psi: ArrayView1<f64>,
+        values: ArrayView1<f64>,
+    ) -> Result<Option<f64>> {
+        use AxisExtrapolation::*;
+
+        // A second knot on the axis would make the ψ grid non-monotonic.
+        if psi.first() == Some(&0.0) {
+            return Ok(Option::None);
+        }
+
+        let needed = self.points_needed();
+        let found = psi.len().min(values.len());
+        if found < needed {
+            return Err(NcError::NotEnoughPoints {
+                name: name.into(),
+                needed,
+                found,
+            });
+        }
+
+        let nodes =
+            |x: fn(f64) -> f64| -> Vec<f64> { psi.iter().take(needed).map(|&p| x(p)).collect() };
+        let first = values.slice(ndarray::s![..needed]);
+
+        let value = match self {
+            None => return Ok(Option::None),
+            Constant(value) => *value,
+            Nearest => values[0],
+            Linear | Quadratic => lagrange_at_zero(&nodes(|p| p), first),
+            SqrtPolynomial(_) => lagrange_at_zero(&nodes(f64::sqrt), first),
+        };
+        Ok(Some(value))
+    }
+}
+
+/// Evaluates at 0 the polynomial passing through the points (`x`, `y`).
+fn lagrange_at_zero(x: &[f64], y: ArrayView1<f64>) -> f64 {
+    (0..x.len())
+        .map(|k| {
+            let weight: f64 = (0..x.len())
+                .filter(|&j| j != k)
+                .map(|j| x[j] / (x[j] - x[k]))
+                .product();
+            weight * y[k]
+        })
+        .sum()
+}
+
+/// Prepends the axis value of a 1D quantity at index 0.
+pub(crate) fn prepend_axis_value(
+    name: &str,
+    psi: ArrayView1<f64>,
+    values: Array1<f64>,
+    method: AxisExtrapolation,
+) -> Result<Array1<f64>> {
+    match method.axis_value(name, psi, values.view())? {
+        Some(axis_value) => {
+            let mut prepend = Array1::from_elem(1, axis_value);
+            // This is not expected to fail since both arrays are 1-dimensional.
+            match prepend.append(Axis(0), values.view()) {
+                Ok(()) => Ok(prepend),
+                Err(_) => unreachable!("Shape mismatch in prepending axis value."),
+            }
+        }
+        None => Ok(values),
+    }
+}
+
+/// Prepends the axis row of a 2D (ψ, θ) quantity at index 0. The row is filled with the
+/// θ-average of each column's axis value.
+pub(crate) fn prepend_axis_row(
+    name: &str,
+    psi: ArrayView1<f64>,
+    values: Array2<f64>,
+    method: AxisExtrapolation,
+) -> Result<Array2<f64>> {
+    let mut sum = 0.0;
+    for column in values.columns() {
+        match method.axis_value(name, psi, column)? {
+            Some(axis_value) => sum += axis_value,
+            None => return Ok(values),
+        }
+    }
+
+    let axis_row = Array2::from_elem((1, values.ncols()), sum / values.ncols() as f64);
+    // This is not expected to fail since both arrays have the same number of columns.
+    match concatenate(Axis(0), &[axis_row.view(), values.view()]) {
+        Ok(arr) => Ok(arr),
+        Err(_) => unreachable!("Shape mismatch in prepending axis row."),
+    }
+}
+
+/// The [`AxisExtrapolation`] used for each of the equilibrium's quantities.
+///
+/// All quantities must either have an axis value or not, otherwise they do not share the same ψ
+/// grid and opening the file fails with [`NcError::ShapeMismatch`].
+///
+/// ```
+/// # use tokamak_netcdf::{AxisExtrapolation, AxisSchema, NcSchema};
+/// let schema = NcSchema::default().with_axis(AxisSchema {
+///     g: AxisExtrapolation::Linear,
+///     b_field: AxisExtrapolation::SqrtPolynomial(2),
+///     ..Default::default()
+/// });
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct AxisSchema {
+    /// ψ coordinate. Defaults to 0.
+    pub psi: AxisExtrapolation,
+    /// Plasma toroidal current **I**. Defaults to 0.
+    pub i: AxisExtrapolation,
+    /// Plasma poloidal current **g**. Defaults to [`AxisExtrapolation::Nearest`].
+    pub g: AxisExtrapolation,
+    /// Magnetic field strength. Defaults to [`AxisExtrapolation::Nearest`].
+    pub b_field: AxisExtrapolation,
+    /// Safety factor. Defaults to [`AxisExtrapolation::Nearest`].
+    pub q: AxisExtrapolation,
+    /// Poloidal flux. Defaults to 0.
+    pub psip: AxisExtrapolation,
+    /// R(ψ, θ) and Z(ψ, θ) maps. Defaults to [`AxisExtrapolation::Nearest`].
+    pub geometry: AxisExtrapolation,
+}
+
+impl AxisSchema {
+    /// Creates an `AxisSchema` that adds no axis values at all, leaving the quantities on the
+    /// file's ψ grid.
+    pub fn none() -> Self {
+        use AxisExtrapolation::None;
+        Self {
+            psi: None,
+            i: None,
+            g: None,
+            b_field: None,
+            q: None,
+            psip: None,
+            geometry: None,
+        }
+    }
+}
+
+impl Default for AxisSchema {
+    fn default() -> Self {
+        use AxisExtrapolation::*;
+        Self {
+            psi: Constant(0.0),
+            i: Constant(0.0),
+            g: Nearest,
+            b_field: Nearest,
+            q: Nearest,
+            psip: Constant(0.0),
+            geometry: Nearest,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use AxisExtrapolation::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_axis_values() -> Result<()> {
+        let psi = array![0.1, 0.2, 0.3, 0.4];
+        let axis_value = |values: Array1<f64>, method: AxisExtrapolation| -> Result<Option<f64>> {
+            method.axis_value("var", psi.view(), values.view())
+        };
+
+        let linear = psi.mapv(|p| 2.0 + 3.0 * p);
+        let quadratic = psi.mapv(|p| 2.0 + 3.0 * p - p * p);
+        let sqrt_cubic = psi.mapv(|p| 2.0 + p.sqrt() + p + p.powf(1.5));
+
+        assert_eq!(axis_value(linear.clone(), None)?, Option::None);
+        assert_eq!(axis_value(linear.clone(), Constant(5.0))?, Some(5.0));
+        assert_eq!(axis_value(linear.clone(), Nearest)?, Some(linear[0]));
+        assert!((axis_value(linear, Linear)?.unwrap() - 2.0).abs() < 1e-12);
+        assert!((axis_value(quadratic, Quadratic)?.unwrap() - 2.0).abs() < 1e-12);
+        assert!((axis_value(sqrt_cubic, SqrtPolynomial(3))?.unwrap() - 2.0).abs() < 1e-12);
+
+        // Grids already starting on the axis get no axis value.
+        let on_axis = array![0.0, 0.1];
+        assert_eq!(
+            Constant(0.0).axis_value("var", on_axis.view(), on_axis.view())?,
+            Option::None
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_not_enough_points() {
+        let psi = array![0.1, 0.2];
+        let values = array![1.0, 2.0];
+        assert!(matches!(
+            Quadratic.axis_value("var", psi.view(), values.view()),
+            Err(NcError::NotEnoughPoints {
+                needed: 3,
+                found: 2,
+                ..
+            })
+        ));
+    }
+
+    #[test]
+    fn test_prepend_axis_row() -> Result<()> {
+        let psi = array![0.1, 0.2];
+        let values = array![[1.0, 2.0, 3.0], [2.0, 2.0, 4.0]];
+
+        let nearest = prepend_axis_row("var", psi.view(), values.clone(), Nearest)?;
+        assert_eq!(nearest.row(0), array![2.0, 2.0, 2.0]);
+        assert_eq!(nearest.slice(ndarray::s![1.., ..]), values);
+
+        // Column-wise linear extrapolation gives [0, 2, 2].
+        let linear = prepend_axis_row("var", psi.view(), values.clone(), Linear)?;
+        assert!(linear.row(0).iter().all(|&x| (x - 4.0 / 3.0).abs() < 1e-12));
+
+        let none = prepend_axis_row("var", psi.view(), values.clone(), None)?;
+        assert_eq!(none, values);
+        Ok(())
+    }
+}
@@ -0,0 +1,314 @@
+//! NetCDF file readers behind the extraction functions: libnetcdf, with the `netcdf` feature,
+//! and the pure-Rust classic format reader, with the `classic` feature.
+
+#[cfg(feature = "netcdf")]
+use crate::NcError;
+#[cfg(feature = "classic")]
+use crate::classic::{self, ClassicFile};
+use crate::{AttributeValue, Result};
+
+/// An open NetCDF file.
+pub(crate) enum NcFile<'b> {
+    /// File read by libnetcdf.
+    #[cfg(feature = "netcdf")]
+    Netcdf(netcdf::File),
+    /// File contents held in memory, read by libnetcdf.
+    #[cfg(feature = "netcdf")]
+    Memory(netcdf::FileMem<'b>),
+    /// Classic or 64-bit offset file, read by the pure-Rust reader.
+    #[cfg(feature = "classic")]
+    Classic(ClassicFile<'b>),
+}
+
+/// A reference to an open NetCDF file, through which its variables and attributes are read.
+#[derive(Clone, Copy)]
+pub(crate) enum NcFileRef<'f> {
+    #[cfg(feature = "netcdf")]
+    Netcdf(&'f netcdf::File),
+    #[cfg(feature = "classic")]
+    Classic(&'f ClassicFile<'f>),
+}
+
+/// A variable of an open NetCDF file.
+pub(crate) enum Variable<'f> {
+    #[cfg(feature = "netcdf")]
+    Netcdf(netcdf::Variable<'f>),
+    #[cfg(feature = "classic")]
+    Classic(&'f ClassicFile<'f>, &'f classic::Variable),
+}
+
+impl<'f> From<&'f NcFile<'f>> for NcFileRef<'f> {
+    fn from(file: &'f NcFile<'f>) -> Self {
+        match file {
+            #[cfg(feature = "netcdf")]
+            NcFile::Netcdf(file) => NcFileRef::Netcdf(file),
+            #[cfg(feature = "netcdf")]
+            NcFile::Memory(file) => NcFileRef::Netcdf(file),
+            #[cfg(feature = "classic")]
+            NcFile::Classic(file) => NcFileRef::Classic(file),
+        }
+    }
+}
+
+#[cfg(feature = "netcdf")]
+impl<'f> From<&'f netcdf::File> for NcFileRef<'f> {
+    fn from(file: &'f netcdf::File) -> Self {
+        NcFileRef::Netcdf(file)
+    }
+}
+
+#[cfg(feature = "netcdf")]
+impl<'f> From<&'f netcdf::FileMut> for NcFileRef<'f> {
+    fn from(file: &'f netcdf::FileMut) -> Self {
+        NcFileRef::Netcdf(file)
+    }
+}
+
+impl<'f> NcFileRef<'f> {
+    /// Returns whether the group at `path` exists. The root group's path is empty.
+    pub(crate) fn has_group(self, path: &str) -> bool {
+        if path.is_empty() {
+            return true;
+        }
+        match self {
+            #[cfg(feature = "netcdf")]
+            NcFileRef::Netcdf(file) => matches!(file.group(path), Ok(Some(_))),
+            // The classic formats have no groups.
+            #[cfg(feature = "classic")]
+            NcFileRef::Classic(_) => false,
+        }
+    }
+
+    /// Finds the variable at `path`.
+    pub(crate) fn variable(self, path: &str) -> Option<Variable<'f>> {
+        // netcdf panics when looking up a variable through a group that does not exist.
+        if let Some((parent, _)) = path.rsplit_once('/')
+            && !self.has_group(parent)
+        {
+            return None;
+        }
+        match self {
+            #[cfg(feature = "netcdf")]
+            NcFileRef::Netcdf(file) => file.variable(path).map(Variable::Netcdf),
+            #[cfg(feature = "classic")]
+            NcFileRef::Classic(file) => file.variable(path).map(|var| Variable::Classic(file, var)),
+        }
+    }
+
+    /// Returns the attributes of the group at `path`, by name.
+    pub(crate) fn group_attributes(self, path: &str) -> Vec<(String, AttributeValue)> {
+        match self {
+            #[cfg(feature = "netcdf")]
+            NcFileRef::Netcdf(file) => {
+                let convert =
+                    |attr: netcdf::Attribute| Some((attr.name().into(), netcdf_attribute(&attr)?));
+                match path {
+                    "" => file.attributes().filter_map(convert).collect(),
+                    _ => match file.group(path) {
+                        Ok(Some(group)) => group.attributes().filter_map(convert).collect(),
+                        _ => Vec::new(),
+                    },
+                }
+            }
+            #[cfg(feature = "classic")]
+            NcFileRef::Classic(file) => match path {
+                "" => file
+                    .attributes
+                    .iter()
+                    .map(|attr| (attr.name.clone(), (&attr.value).into()))
+                    .collect(),
+                _ => Vec::new(),
+            },
+        }
+    }
+}
+
+impl Variable<'_> {
+    pub(crate) fn name(&self) -> String {
+        match self {
+            #[cfg(feature = "netcdf")]
+            Variable::Netcdf(var) => var.name(),
+            #[cfg(feature = "classic")]
+            Variable::Classic(_, var) => var.name.clone(),
+        }
+    }
+
+    /// Returns the names and lengths of the variable's dimensions.
+    pub(crate) fn dimensions(&self) -> Vec<(String, usize)> {
+        match self {
+            #[cfg(feature = "netcdf")]
+            Variable::Netcdf(var) => var
+                .dimensions()
+                .iter()
+                .map(|dim| (dim.name(), dim.len()))
+                .collect(),
+            #[cfg(feature = "classic")]
+            Variable::Classic(file, var) => file
+                .dimensions(var)
+                .iter()
+                .map(|dim| (dim.name.clone(), dim.len))
+                .collect(),
+        }
+    }
+
+    pub(crate) fn attribute(&self, name: &str) -> Option<AttributeValue> {
+        match self {
+            #[cfg(feature = "netcdf")]
+            Variable::Netcdf(var) => netcdf_attribute(&var.attribute(name)?),
+            #[cfg(feature = "classic")]
+            Variable::Classic(_, var) => var
+                .attributes
+                .iter()
+                .find(|attr| attr.name == name)
+                .map(|attr| (&attr.value).into()),
+        }
+    }
+
+    /// Reads a string variable's value: the first string of an `NC_STRING` variable, or the
+    /// characters of a `char` one. Returns `None` for other types.
+    pub(crate) fn text(&self) -> Option<Box<str>> {
+        match self {
+            #[cfg(feature = "netcdf")]
+            Variable::Netcdf(var) => match var.vartype() {
+                netcdf::types::NcVariableType::String => {
+                    let first = vec![netcdf::Extent::Index(0); var.dimensions().len()];
+                    var.get_string(first.as_slice()).ok().map(Into::into)
+                }
+                netcdf::types::NcVariableType::Char => {
+                    let bytes = var.get_raw_values(..).ok()?;
+                    let text = String::from_utf8_lossy(&bytes);
+                    Some(text.trim_end_matches(['\0', ' ']).into())
+                }
+                _ => None,
+            },
+            // The classic formats are only read for the crate's own layout, which has no string
+            // variables.
+            #[cfg(feature = "classic")]
+            Variable::Classic(..) => None,
+        }
+    }
+
+    /// Reads the variable's values as `f64`, in row-major order. If `index` is given, only that
+    /// index of the first dimension is read.
+    pub(crate) fn values(&self, index: Option<usize>) -> Result<Vec<f64>> {
+        match self {
+            #[cfg(feature = "netcdf")]
+            Variable::Netcdf(var) => {
+                let mut extents = vec![netcdf::Extent::from(..); var.dimensions().len()];
+                if let Some(index) = index {
+                    extents[0] = netcdf::Extent::Index(index);
+                }
+                var.get_values::<f64, _>(extents.as_slice()).map_err(|err| {
+                    NcError::GetValuesError {
+                        name: var.name().into(),
+                        source: err,
+                    }
+                })
+            }
+            #[cfg(feature = "classic")]
+            Variable::Classic(file, var) => file.values(var, index),
+        }
+    }
+}
+
+/// Reads an attribute's value. Returns `None` if it cannot be read.
+#[cfg(feature = "netcdf")]
+fn netcdf_attribute(attr: &netcdf::Attribute) -> Option<AttributeValue> {
+    attr.value().ok().map(Into::into)
+}
+
+#[cfg(feature = "classic")]
+impl From<&classic::AttributeValue> for AttributeValue {
+    /// Converts a classic attribute's value like libnetcdf reads it: single values are scalars.
+    fn from(value: &classic::AttributeValue) -> Self {
+        use classic::AttributeValue as Classic;
+
+        match value {
+            Classic::Text(text) => Self::Str(text.clone()),
+            Classic::Bytes(values) => match values.as_slice() {
+                &[x] => Self::Schar(x),
+                _ => Self::Schars(values.clone()),
+            },
+            Classic::Shorts(values) => match values.as_slice() {
+                &[x] => Self::Short(x),
+                _ => Self::Shorts(values.clone()),
+            },
+            Classic::Ints(values) => match values.as_slice() {
+                &[x] => Self::Int(x),
+                _ => Self::Ints(values.clone()),
+            },
+            Classic::Floats(values) => match values.as_slice() {
+                &[x] => Self::Float(x),
+                _ => Self::Floats(values.clone()),
+            },
+            Classic::Doubles(values) => match values.as_slice() {
+                &[x] => Self::Double(x),
+                _ => Self::Doubles(values.clone()),
+            },
+        }
+    }
+}
+
+#[cfg(feature = "netcdf")]
+impl From<netcdf::AttributeValue> for AttributeValue {
+    fn from(value: netcdf::AttributeValue) -> Self {
+        use netcdf::AttributeValue as Nc;
+
+        match value {
+            Nc::Uchar(x) => Self::Uchar(x),
+            Nc::Uchars(x) => Self::Uchars(x),
+            Nc::Schar(x) => Self::Schar(x),
+            Nc::Schars(x) => Self::Schars(x),
+            Nc::Ushort(x) => Self::Ushort(x),
+            Nc::Ushorts(x) => Self::Ushorts(x),
+            Nc::Short(x) => Self::Short(x),
+            Nc::Shorts(x) => Self::Shorts(x),
+            Nc::Uint(x) => Self::Uint(x),
+            Nc::Uints(x) => Self::Uints(x),
+            Nc::Int(x) => Self::Int(x),
+            Nc::Ints(x) => Self::Ints(x),
+            Nc::Ulonglong(x) => Self::Ulonglong(x),
+            Nc::Ulonglongs(x) => Self::Ulonglongs(x),
+            Nc::Longlong(x) => Self::Longlong(x),
+            Nc::Longlongs(x) => Self::Longlongs(x),
+            Nc::Float(x) => Self::Float(x),
+            Nc::Floats(x) => Self::Floats(x),
+            Nc::Double(x) => Self::Double(x),
+            Nc::Doubles(x) => Self::Doubles(x),
+            Nc::Str(x) => Self::Str(x),
+            Nc::Strs(x) => Self::Strs(x),
+        }
+    }
+}
+
+#[cfg(feature = "netcdf")]
+impl From<AttributeValue> for netcdf::AttributeValue {
+    fn from(value: AttributeValue) -> Self {
+        use AttributeValue as Value;
+
+        match value {
+            Value::Uchar(x) => Self::Uchar(x),
+            Value::Uchars(x) => Self::Uchars(x),
+            Value::Schar(x) => Self::Schar(x),
+            Value::Schars(x) => Self::Schars(x),
+            Value::Ushort(x) => Self::Ushort(x),
+            Value::Ushorts(x) => Self::Ushorts(x),
+            Value::Short(x) => Self::Short(x),
+            Value::Shorts(x) => Self::Shorts(x),
+            Value::Uint(x) => Self::Uint(x),
+            Value::Uints(x) => Self::Uints(x),
+            Value::Int(x) => Self::Int(x),
+            Value::Ints(x) => Self::Ints(x),
+            Value::Ulonglong(x) => Self::Ulonglong(x),
+            Value::Ulonglongs(x) => Self::Ulonglongs(x),
+            Value::Longlong(x) => Self::Longlong(x),
+            Value::Longlongs(x) => Self::Longlongs(x),
+            Value::Float(x) => Self::Float(x),
+            Value::Floats(x) => Self::Floats(x),
+            Value::Double(x) => Self::Double(x),
+            Value::Doubles(x) => Self::Doubles(x),
+            Value::Str(x) => Self::Str(x),
+            Value::Strs(x) => Self::Strs(x),
+        }
+    }
+}
@@ -1,6 +1,9 @@
-use ndarray::Array2;
+use ndarray::{Array2, ArrayView1};
 
-use crate::{Result, extract::extract_2d_var};
+use crate::{
+    NcSchema, Result,
+    extract::{NcSource, extract_2d_var_with_axis},
+};
 
 /// Representation of the equilibrium's magnetic field **B**.
 pub struct Bfield {
@@ -12,11 +15,23 @@ pub struct Bfield {
 
 impl Bfield {
     /// Creates a `Bfield` containing the magnetic field data from the NetCDF file.
-    pub(crate) fn build(f: &netcdf::File) -> Result<Self> {
-        let b = extract_2d_var(f, "b_field_norm")?;
+    pub(crate) fn build(
+        f: &NcSource,
+        schema: &NcSchema,
+        file_psi: ArrayView1<f64>,
+    ) -> Result<Self> {
+        // Extrapolate to the axis, to match the ψ coordinate.
+        let coords = (&*schema.psi, &*schema.theta);
+        let b =
+            extract_2d_var_with_axis(f, &schema.b_field, coords, file_psi, schema.axis.b_field)?;
 
+        Ok(Self::new(b))
+    }
+
+    /// Creates a `Bfield` from the magnetic field values, with shape (ψ, θ).
+    pub(crate) fn new(b: Array2<f64>) -> Self {
         let shape: (usize, usize) = (b.dim().0, b.dim().1);
-        Ok(Bfield { b, shape })
+        Bfield { b, shape }
     }
 }
 
@@ -32,19 +47,29 @@ impl std::fmt::Debug for Bfield {
             self.shape,
         )?;
 
-        for row in [0, 1, rows - 2, rows - 1] {
-            writeln!(
-                f,
-                "    ({:.7}, {:.7}, ..., {:.7}, {:.7})",
-                self.b[[row, 0]],
-                self.b[[row, 1]],
-                self.b[[row, columns - 2]],
-                self.b[[row, columns - 1]]
-            )?;
-            if row == 1 {
+        for row in ends(rows) {
+            let Some(row) = row else {
                 writeln!(f, "    (........., ........., ..., ........., .........) ")?;
-            }
+                continue;
+            };
+            let values: Vec<String> = ends(columns)
+                .into_iter()
+                .map(|column| match column {
+                    Some(column) => format!("{:.7}", self.b[[row, column]]),
+                    None => "...".into(),
+                })
+                .collect();
+            writeln!(f, "    ({})", values.join(", "))?;
         }
         write!(f, "}}")
     }
 }
+
+/// Indices of the first and last two of `len` elements, with `None` for the elided ones between
+/// them, or of all of them if there are at most 4.
+fn ends(len: usize) -> Vec<Option<usize>> {
+    match len {
+        0..=4 => (0..len).map(Some).collect(),
+        _ => vec![Some(0), Some(1), None, Some(len - 2), Some(len - 1)],
+    }
+}
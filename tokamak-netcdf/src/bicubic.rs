@@ -0,0 +1,281 @@
+//! Bicubic interpolation of the magnetic field strength, periodic in θ.
+
+use std::f64::consts::TAU;
+
+use ndarray::{Array2, Array3, Array4, ArrayView1, s};
+
+use crate::accel::hunt;
+use crate::spline::hermite;
+use crate::validate::{check_shape, incomplete_period, period_len};
+use crate::{
+    Accelerator, Bfield, Coords, Cubic, DomainPolicy, Interpolation, NcData, NcError, NcSchema,
+    Result,
+};
+
+/// Bicubic interpolation of B(ψ, θ) on the (ψ, θ) grid of an equilibrium.
+///
+/// On each grid cell, B is the bicubic polynomial matching B, ∂B/∂ψ, ∂B/∂θ and ∂²B/∂ψ∂θ on the
+/// cell's corners. The derivatives on the corners are the slopes an [`Interpolation`] scheme
+/// assigns along ψ, and periodically along θ, to the cell's ends. With the [`Linear`] scheme, B
+/// is bilinear on each cell; with the others, B and its first derivatives are continuous.
+///
+/// [`Linear`]: crate::Linear
+#[derive(Clone)]
+pub struct BfieldSpline {
+    /// Name of the ψ variable, for errors.
+    psi_name: Box<str>,
+    psi: Vec<f64>,
+    /// θ values of one period, with the endpoint closing it.
+    theta: Vec<f64>,
+    /// B on the grid points, with shape (ψ, θ).
+    b: Array2<f64>,
+    /// ∂B/∂ψ on the (left, right) ends of each ψ cell, with shape (ψ cell, end, θ).
+    dpsi: Array3<f64>,
+    /// ∂B/∂θ on the (left, right) ends of each θ cell, with shape (ψ, θ cell, end).
+    dtheta: Array3<f64>,
+    /// ∂²B/∂ψ∂θ on the ends of each cell, with shape (ψ cell, ψ end, θ cell, θ end).
+    dpsi_dtheta: Array4<f64>,
+    domain: DomainPolicy,
+}
+
+impl NcData {
+    /// Creates a bicubic, θ-periodic interpolation of B on the equilibrium's (ψ, θ) grid,
+    /// including the axis value if extrapolated, whose derivatives on the grid points are those
+    /// of natural cubic splines along ψ, and of periodic cubic splines along θ.
+    ///
+    /// The θ grid must cover a full period, with or without the 2π endpoint, and B's values on
+    /// the endpoint are ignored. The ψ grid must have at least 2 points.
+    ///
+    /// ```
+    /// # use tokamak_netcdf::{CircularTokamak, NcError};
+    /// # fn main() -> Result<(), NcError> {
+    /// let nc_data = CircularTokamak::default().to_nc_data()?;
+    /// let spline = nc_data.bfield_spline()?;
+    /// let (b, db_dpsi, db_dtheta, d2b_dpsi_dtheta) = spline.eval(0.03, 1.0)?;
+    /// assert!((b - spline.eval(0.03, 1.0 + std::f64::consts::TAU)?.0).abs() < 1e-12);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bfield_spline(&self) -> Result<BfieldSpline> {
+        self.bfield_spline_with(&Cubic)
+    }
+
+    /// Creates a θ-periodic interpolation of B on the equilibrium's (ψ, θ) grid like
+    /// [`NcData::bfield_spline`], with the interpolation `scheme` along both ψ and θ.
+    ///
+    /// Every scheme passes through the same values on the grid points.
+    ///
+    /// ```
+    /// # use tokamak_netcdf::{Akima, CircularTokamak, NcError};
+    /// # fn main() -> Result<(), NcError> {
+    /// let nc_data = CircularTokamak::default().to_nc_data()?;
+    /// let spline = nc_data.bfield_spline_with(&Akima)?;
+    /// let (psi, theta) = (nc_data.coords.psi[2], nc_data.coords.theta[5]);
+    /// assert_eq!(spline.eval(psi, theta)?.0, nc_data.bfield.b[[2, 5]]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bfield_spline_with<I>(&self, scheme: &I) -> Result<BfieldSpline>
+    where
+        I: Interpolation + ?Sized,
+    {
+        self.bfield.spline_with(&self.coords, &self.schema, scheme)
+    }
+}
+
+impl Bfield {
+    /// Creates a θ-periodic interpolation of B on the (ψ, θ) grid of `coords`, like
+    /// [`NcData::bfield_spline_with`]. Errors name the θ and B variables after `schema`.
+    pub(crate) fn spline_with<I>(
+        &self,
+        coords: &Coords,
+        schema: &NcSchema,
+        scheme: &I,
+    ) -> Result<BfieldSpline>
+    where
+        I: Interpolation + ?Sized,
+    {
+        let (psi, theta) = (coords.psi.view(), coords.theta.view());
+        if let Some(span) = incomplete_period(theta.as_slice().unwrap_or(&[])) {
+            return Err(NcError::IncompletePeriod {
+                name: schema.theta.clone(),
+                span,
+            });
+        }
+        if psi.len() < 2 {
+            return Err(NcError::InvalidGrid(
+                "bicubic interpolation needs at least 2 psi points".into(),
+            ));
+        }
+        check_shape(&schema.b_field, &[psi.len(), theta.len()], self.b.shape())?;
+
+        let n = period_len(theta);
+        let psi = psi.to_vec();
+        let mut theta = theta.slice(s![..n]).to_vec();
+        let b = self.b.slice(s![.., ..n]).to_owned();
+
+        // Slopes on the ends of each cell, with shape (cell, end).
+        let along_psi =
+            |values: ArrayView1<f64>| Array2::from(scheme.slopes(&psi, &values.to_vec(), None));
+        let along_theta = |values: ArrayView1<f64>| {
+            Array2::from(scheme.slopes(&theta, &values.to_vec(), Some(TAU)))
+        };
+
+        let mut dpsi = Array3::zeros((psi.len() - 1, 2, n));
+        for (l, column) in b.columns().into_iter().enumerate() {
+            dpsi.slice_mut(s![.., .., l]).assign(&along_psi(column));
+        }
+        let mut dtheta = Array3::zeros((psi.len(), n, 2));
+        for (k, row) in b.rows().into_iter().enumerate() {
+            dtheta.slice_mut(s![k, .., ..]).assign(&along_theta(row));
+        }
+        let mut dpsi_dtheta = Array4::zeros((psi.len() - 1, 2, n, 2));
+        for j in 0..n {
+            for c in 0..2 {
+                let slopes = along_psi(dtheta.slice(s![.., j, c]));
+                dpsi_dtheta.slice_mut(s![.., .., j, c]).assign(&slopes);
+            }
+        }
+
+        // The last cell closes the period.
+        theta.push(theta[0] + TAU);
+        Ok(BfieldSpline {
+            psi_name: schema.psi.clone(),
+            psi,
+            theta,
+            b,
+            dpsi,
+            dtheta,
+            dpsi_dtheta,
+            domain: DomainPolicy::Extend,
+        })
+    }
+}
+
+impl BfieldSpline {
+    /// Sets the behaviour outside the ψ grid's span, which is [`DomainPolicy::Extend`] by
+    /// default, extending the cells of the first or last surface.
+    pub fn with_domain(mut self, policy: DomainPolicy) -> Self {
+        self.domain = policy;
+        self
+    }
+
+    /// Evaluates B and its derivatives at (`psi`, `theta`), returning
+    /// (B, ∂B/∂ψ, ∂B/∂θ, ∂²B/∂ψ∂θ).
+    ///
+    /// θ is taken modulo 2π, so that θ and θ + 2π give the same results. Outside the ψ grid,
+    /// the spline's [`DomainPolicy`] applies.
+    pub fn eval(&self, psi: f64, theta: f64) -> Result<(f64, f64, f64, f64)> {
+        self.eval_with(psi, theta, &mut Accelerator::new())
+    }
+
+    /// Evaluates B and its derivatives at (`psi`, `theta`) like [`BfieldSpline::eval`],
+    /// starting the search for the cell from the last one of `acc`.
+    pub fn eval_with(
+        &self,
+        psi: f64,
+        theta: f64,
+        acc: &mut Accelerator,
+    ) -> Result<(f64, f64, f64, f64)> {
+        // Safe unwrap(); there are at least 2 ψ points.
+        let span = (self.psi[0], *self.psi.last().unwrap());
+        let (psi, vacuum) = self.domain.apply(&self.psi_name, psi, span)?;
+        let n = self.theta.len() - 1;
+        let first = self.theta[0];
+        let theta = first + (theta - first).rem_euclid(TAU);
+
+        acc.psi = hunt(&self.psi, psi, acc.psi);
+        acc.theta = hunt(&self.theta, theta, acc.theta);
+        let (i, j) = (acc.psi, acc.theta);
+        let h_psi = self.psi[i + 1] - self.psi[i];
+        // The last cell closes the period.
+        let h_theta = self.theta[j + 1] - self.theta[j];
+
+        let (basis_psi, slope_psi) = hermite((psi - self.psi[i]) / h_psi);
+        let (basis_theta, slope_theta) = hermite((theta - self.theta[j]) / h_theta);
+
+        let mut result = [0.0; 4];
+        for (a, k) in [i, i + 1].into_iter().enumerate() {
+            for (c, l) in [j, (j + 1) % n].into_iter().enumerate() {
+                // Each corner value's (ψ basis, θ basis) indices.
+                let terms = [
+                    (self.b[[k, l]], a, c),
+                    (h_psi * self.dpsi[[i, a, l]], 2 + a, c),
+                    (h_theta * self.dtheta[[k, j, c]], a, 2 + c),
+                    (
+                        h_psi * h_theta * self.dpsi_dtheta[[i, a, j, c]],
+                        2 + a,
+                        2 + c,
+                    ),
+                ];
+                for (value, p, t) in terms {
+                    result[0] += value * basis_psi[p] * basis_theta[t];
+                    result[1] += value * slope_psi[p] * basis_theta[t] / h_psi;
+                    result[2] += value * basis_psi[p] * slope_theta[t] / h_theta;
+                    result[3] += value * slope_psi[p] * slope_theta[t] / (h_psi * h_theta);
+                }
+            }
+        }
+        if vacuum {
+            (result[1], result[3]) = (0.0, 0.0);
+        }
+        Ok(result.into())
+    }
+}
+
+impl std::fmt::Debug for BfieldSpline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("BfieldSpline")
+            .field("psi_len", &self.psi.len())
+            .field("theta_len", &(self.theta.len() - 1))
+            .field("domain", &self.domain)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ndarray::{Array1, array};
+
+    #[test]
+    fn test_endpoint() -> Result<()> {
+        // Linear in ψ, so that only the θ interpolation is approximate.
+        let f = |psi: f64, theta: f64| (1.0 + 0.5 * psi) * (1.0 + 0.1 * theta.cos());
+        let psi = array![0.0, 0.5, 1.0];
+        let with_endpoint = Array1::linspace(0.0, TAU, 65);
+        let without_endpoint = with_endpoint.slice(s![..64]).to_owned();
+
+        let splines = [with_endpoint, without_endpoint].map(|theta| {
+            let b = Array2::from_shape_fn((3, theta.len()), |(k, j)| f(psi[k], theta[j]));
+            let coords = Coords::new(psi.clone(), theta, false);
+            Bfield::new(b).spline_with(&coords, &NcSchema::default(), &Cubic)
+        });
+        let [with_endpoint, without_endpoint] = splines;
+        let (with_endpoint, without_endpoint) = (with_endpoint?, without_endpoint?);
+
+        for (psi, theta) in [(0.25, 0.0), (0.7, 3.0), (1.0, 6.27), (0.1, -1.0)] {
+            let a = with_endpoint.eval(psi, theta)?;
+            let b = without_endpoint.eval(psi, theta)?;
+            assert_eq!(a, b);
+            assert!((a.0 - f(psi, theta)).abs() < 1e-6);
+            assert!((a.1 - 0.5 * (1.0 + 0.1 * theta.cos())).abs() < 1e-6);
+            assert!((a.2 + 0.1 * (1.0 + 0.5 * psi) * theta.sin()).abs() < 1e-5);
+            assert!((a.3 + 0.05 * theta.sin()).abs() < 1e-5);
+        }
+
+        let half = Coords::new(psi.clone(), array![0.0, 1.0, 2.0], false);
+        let bfield = Bfield::new(Array2::zeros((3, 3)));
+        let schema = NcSchema::new().with_theta("theta_b").with_b_field("bmod");
+        assert!(matches!(
+            bfield.spline_with(&half, &schema, &Cubic),
+            Err(NcError::IncompletePeriod { name, .. }) if &*name == "theta_b"
+        ));
+        let full = Coords::new(psi, Array1::linspace(0.0, 6.0, 4), false);
+        assert!(matches!(
+            bfield.spline_with(&full, &schema, &Cubic),
+            Err(NcError::ShapeMismatch { name, .. }) if &*name == "bmod"
+        ));
+        Ok(())
+    }
+}
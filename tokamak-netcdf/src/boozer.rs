@@ -0,0 +1,341 @@
+//! Mapping of an axisymmetric equilibrium given as ψp(R, Z) to Boozer coordinates.
+//!
+//! In an axisymmetric equilibrium the Boozer Jacobian is proportional to 1/B² on each flux
+//! surface, so along a surface's poloidal contour
+//!
+//! dθ/dl ∝ B² / Bp,
+//!
+//! normalised so that θ increases by 2π over the contour. The covariant components are
+//! g = R Bφ = F(ψp) and I = ∮ Bp dl / 2π, and the toroidal flux follows from dψ = q dψp.
+
+use std::f64::consts::TAU;
+
+use ndarray::{Array1, Array2};
+
+use crate::raw::RawEquilibrium;
+use crate::{NcError, Result};
+
+/// Resolution of the (ψ, θ) grid an equilibrium is mapped onto.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BoozerGrid {
+    /// Number of flux surfaces, excluding the axis.
+    pub psi_len: usize,
+    /// Number of θ points. The grid is uniform in \[0, 2π) and does not include 2π.
+    pub theta_len: usize,
+    /// Outermost surface, in normalised poloidal flux. Values slightly below 1 avoid the
+    /// X-point of diverted equilibria.
+    pub psi_n_max: f64,
+}
+
+impl Default for BoozerGrid {
+    fn default() -> Self {
+        Self {
+            psi_len: 100,
+            theta_len: 128,
+            psi_n_max: 0.99,
+        }
+    }
+}
+
+/// An axisymmetric equilibrium given as the poloidal flux on a uniform (R, Z) grid, in SI units.
+pub(crate) struct FluxMap {
+    /// Uniform, increasing R grid in \[*m*\].
+    pub(crate) r: Array1<f64>,
+    /// Uniform, increasing Z grid in \[*m*\].
+    pub(crate) z: Array1<f64>,
+    /// Poloidal flux per radian in \[*Wb/rad*\], with shape (Z, R).
+    pub(crate) psi: Array2<f64>,
+    /// Magnetic axis (R, Z) in \[*m*\].
+    pub(crate) axis: (f64, f64),
+    /// Poloidal flux on the axis.
+    pub(crate) psi_axis: f64,
+    /// Poloidal flux on the boundary.
+    pub(crate) psi_boundary: f64,
+    /// F = R Bφ in \[*T m*\], on a uniform normalised poloidal flux grid from the axis to the
+    /// boundary.
+    pub(crate) f: Array1<f64>,
+    /// Safety factor, on the same grid as `f`.
+    pub(crate) q: Array1<f64>,
+}
+
+impl FluxMap {
+    /// Maps the equilibrium onto `grid`, normalising B by its value on the axis and lengths by
+    /// the axis' major radius.
+    pub(crate) fn to_raw(&self, grid: &BoozerGrid) -> Result<RawEquilibrium> {
+        if grid.psi_len == 0 || grid.theta_len == 0 {
+            return Err(NcError::InvalidGrid("grid lengths must be positive".into()));
+        }
+        if !(grid.psi_n_max > 0.0 && grid.psi_n_max <= 1.0) {
+            return Err(NcError::InvalidGrid("psi_n_max must be in (0, 1]".into()));
+        }
+        if self.f.len() < 2 || self.q.len() != self.f.len() {
+            return Err(NcError::InvalidGrid(
+                "flux profiles need at least 2 points".into(),
+            ));
+        }
+
+        let (raxis, _) = self.axis;
+        let baxis = self.f[0].abs() / raxis;
+        let psip_norm = baxis * raxis * raxis;
+        let current_norm = baxis * raxis;
+        let psip_span = (self.psi_boundary - self.psi_axis).abs();
+
+        // Toroidal flux on the profiles' grid, from dψ = q dψp.
+        let dpsip = psip_span / (self.q.len() - 1) as f64;
+        let mut psi_tor = Array1::<f64>::zeros(self.q.len());
+        for n in 1..self.q.len() {
+            psi_tor[n] = psi_tor[n - 1] + 0.5 * (self.q[n - 1].abs() + self.q[n].abs()) * dpsip;
+        }
+
+        let psi_n: Vec<f64> = (1..=grid.psi_len)
+            .map(|k| grid.psi_n_max * k as f64 / grid.psi_len as f64)
+            .collect();
+        let theta =
+            Array1::from_shape_fn(grid.theta_len, |j| TAU * j as f64 / grid.theta_len as f64);
+
+        let mut b = Array2::<f64>::zeros((grid.psi_len, grid.theta_len));
+        let mut r = Array2::<f64>::zeros((grid.psi_len, grid.theta_len));
+        let mut z = Array2::<f64>::zeros((grid.psi_len, grid.theta_len));
+        let mut i = Array1::<f64>::zeros(grid.psi_len);
+        let mut g = Array1::<f64>::zeros(grid.psi_len);
+        let mut q = Array1::<f64>::zeros(grid.psi_len);
+        let mut psi = Array1::<f64>::zeros(grid.psi_len);
+        let mut psip = Array1::<f64>::zeros(grid.psi_len);
+
+        let n_rays = (4 * grid.theta_len).max(128);
+        let mut rho = vec![0.0; n_rays];
+
+        for (k, &target) in psi_n.iter().enumerate() {
+            let f = interp_uniform(&self.f, target);
+            let contour = self.trace_surface(target, &mut rho)?;
+            let surface = BoozerSurface::new(&contour, f);
+
+            for (j, &theta_j) in theta.iter().enumerate() {
+                b[[k, j]] = surface.interp(&surface.b, theta_j) / baxis;
+                r[[k, j]] = surface.interp(&surface.r, theta_j) / raxis;
+                z[[k, j]] = surface.interp(&surface.z, theta_j) / raxis;
+            }
+            i[k] = surface.bp_circulation / TAU / current_norm;
+            g[k] = f.abs() / current_norm;
+            q[k] = interp_uniform(&self.q, target).abs();
+            psi[k] = interp_uniform(&psi_tor, target) / psip_norm;
+            psip[k] = target * psip_span / psip_norm;
+        }
+
+        Ok(RawEquilibrium {
+            baxis,
+            raxis,
+            psi,
+            theta,
+            i,
+            g,
+            b,
+            q: Some(q),
+            psip: Some(psip),
+            rz: Some((r, z)),
+        })
+    }
+
+    /// Finds the contour of the surface with normalised poloidal flux `target`, as the points
+    /// where rays from the axis cross it. `rho` holds each ray's crossing with the previous,
+    /// inner surface, and is updated with the new ones.
+    fn trace_surface(&self, target: f64, rho: &mut [f64]) -> Result<Vec<ContourPoint>> {
+        let n_rays = rho.len();
+        let step = 0.5 * (self.r[1] - self.r[0]).min(self.z[1] - self.z[0]);
+        let (r0, z0) = self.axis;
+
+        let mut contour = Vec::with_capacity(n_rays);
+        for (m, rho_m) in rho.iter_mut().enumerate() {
+            let angle = TAU * m as f64 / n_rays as f64;
+            let (cos, sin) = (angle.cos(), angle.sin());
+            let point = |rho: f64| (r0 + rho * cos, z0 + rho * sin);
+            let psi_n_at = |rho: f64| {
+                let (r, z) = point(rho);
+                self.psi_n(r, z)
+            };
+
+            // March outwards until the surface is crossed, then bisect.
+            let mut lo = *rho_m;
+            let mut hi = lo + step;
+            loop {
+                match psi_n_at(hi) {
+                    Some(psi_n) if psi_n >= target => break,
+                    Some(_) => (lo, hi) = (hi, hi + step),
+                    None => return Err(NcError::SurfaceNotFound(target)),
+                }
+            }
+            for _ in 0..60 {
+                let mid = 0.5 * (lo + hi);
+                // Safe unwrap(); mid lies between two points inside the grid.
+                match psi_n_at(mid).unwrap() >= target {
+                    true => hi = mid,
+                    false => lo = mid,
+                }
+            }
+            *rho_m = 0.5 * (lo + hi);
+
+            let (r, z) = point(*rho_m);
+            // Safe unwrap(); the point lies inside the grid.
+            let (_, dpsi_dr, dpsi_dz) = self.eval(r, z).unwrap();
+            contour.push(ContourPoint {
+                r,
+                z,
+                bp: dpsi_dr.hypot(dpsi_dz) / r,
+            });
+        }
+        Ok(contour)
+    }
+
+    /// Normalised poloidal flux at (R, Z), or `None` outside the grid.
+    fn psi_n(&self, r: f64, z: f64) -> Option<f64> {
+        let (psi, _, _) = self.eval(r, z)?;
+        Some((psi - self.psi_axis) / (self.psi_boundary - self.psi_axis))
+    }
+
+    /// Poloidal flux and its R and Z derivatives at (R, Z), from a bicubic (Catmull-Rom)
+    /// interpolation. Returns `None` outside the grid.
+    fn eval(&self, r: f64, z: f64) -> Option<(f64, f64, f64)> {
+        let (ir, wr, dwr) = catmull_rom(&self.r, r)?;
+        let (iz, wz, dwz) = catmull_rom(&self.z, z)?;
+
+        let (mut psi, mut dpsi_dr, mut dpsi_dz) = (0.0, 0.0, 0.0);
+        for a in 0..4 {
+            for c in 0..4 {
+                let value = self.psi[[iz[a], ir[c]]];
+                psi += wz[a] * wr[c] * value;
+                dpsi_dr += wz[a] * dwr[c] * value;
+                dpsi_dz += dwz[a] * wr[c] * value;
+            }
+        }
+        Some((psi, dpsi_dr, dpsi_dz))
+    }
+}
+
+/// A point on a flux surface's contour.
+struct ContourPoint {
+    r: f64,
+    z: f64,
+    /// Poloidal magnetic field strength.
+    bp: f64,
+}
+
+/// A flux surface's quantities as functions of the Boozer θ.
+struct BoozerSurface {
+    /// Boozer θ of each contour point, closed with 2π.
+    theta: Vec<f64>,
+    b: Vec<f64>,
+    r: Vec<f64>,
+    z: Vec<f64>,
+    /// ∮ Bp dl over the contour.
+    bp_circulation: f64,
+}
+
+impl BoozerSurface {
+    /// Calculates the Boozer θ along a closed contour, from dθ/dl ∝ B² / Bp.
+    fn new(contour: &[ContourPoint], f: f64) -> Self {
+        let n = contour.len();
+        let b: Vec<f64> = contour.iter().map(|p| (f / p.r).hypot(p.bp)).collect();
+        let weight: Vec<f64> = (0..n).map(|m| b[m] * b[m] / contour[m].bp).collect();
+
+        let mut theta = vec![0.0; n + 1];
+        let mut bp_circulation = 0.0;
+        for m in 0..n {
+            let next = (m + 1) % n;
+            let dl = (contour[next].r - contour[m].r).hypot(contour[next].z - contour[m].z);
+            theta[m + 1] = theta[m] + 0.5 * (weight[m] + weight[next]) * dl;
+            bp_circulation += 0.5 * (contour[m].bp + contour[next].bp) * dl;
+        }
+        let total = theta[n];
+        theta.iter_mut().for_each(|t| *t *= TAU / total);
+
+        let closed = |values: Vec<f64>| -> Vec<f64> {
+            let first = values[0];
+            values.into_iter().chain(std::iter::once(first)).collect()
+        };
+        Self {
+            theta,
+            b: closed(b),
+            r: closed(contour.iter().map(|p| p.r).collect()),
+            z: closed(contour.iter().map(|p| p.z).collect()),
+            bp_circulation,
+        }
+    }
+
+    /// Linearly interpolates `values` at the Boozer `theta`, in \[0, 2π\].
+    fn interp(&self, values: &[f64], theta: f64) -> f64 {
+        let idx = self
+            .theta
+            .partition_point(|&t| t <= theta)
+            .clamp(1, self.theta.len() - 1);
+        let (t0, t1) = (self.theta[idx - 1], self.theta[idx]);
+        let u = (theta - t0) / (t1 - t0);
+        values[idx - 1] + u * (values[idx] - values[idx - 1])
+    }
+}
+
+/// Linearly interpolates values given on a uniform grid over \[0, 1\].
+fn interp_uniform(values: &Array1<f64>, x: f64) -> f64 {
+    let last = values.len() - 1;
+    let t = x.clamp(0.0, 1.0) * last as f64;
+    let idx = (t.floor() as usize).min(last - 1);
+    let u = t - idx as f64;
+    values[idx] + u * (values[idx + 1] - values[idx])
+}
+
+/// Indices, weights and derivative weights of the 4 grid points of a Catmull-Rom interpolation
+/// at `x`, on a uniform grid. Indices are clamped at the grid edges. Returns `None` outside the
+/// grid.
+fn catmull_rom(grid: &Array1<f64>, x: f64) -> Option<([usize; 4], [f64; 4], [f64; 4])> {
+    let n = grid.len();
+    let dx = grid[1] - grid[0];
+    let t = (x - grid[0]) / dx;
+    if !(0.0..=(n - 1) as f64).contains(&t) {
+        return None;
+    }
+
+    let i = (t.floor() as usize).min(n - 2);
+    let u = t - i as f64;
+    let idx = [i.saturating_sub(1), i, i + 1, (i + 2).min(n - 1)];
+    let (u2, u3) = (u * u, u * u * u);
+    let weights = [
+        0.5 * (-u3 + 2.0 * u2 - u),
+        0.5 * (3.0 * u3 - 5.0 * u2 + 2.0),
+        0.5 * (-3.0 * u3 + 4.0 * u2 + u),
+        0.5 * (u3 - u2),
+    ];
+    let dweights = [
+        0.5 * (-3.0 * u2 + 4.0 * u - 1.0) / dx,
+        0.5 * (9.0 * u2 - 10.0 * u) / dx,
+        0.5 * (-9.0 * u2 + 8.0 * u + 1.0) / dx,
+        0.5 * (3.0 * u2 - 2.0 * u) / dx,
+    ];
+    Some((idx, weights, dweights))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_catmull_rom() {
+        let grid = Array1::linspace(0.0, 1.0, 11);
+        let quadratic = grid.mapv(|x| x * x);
+
+        let (idx, w, dw) = catmull_rom(&grid, 0.43).unwrap();
+        let value: f64 = (0..4).map(|a| w[a] * quadratic[idx[a]]).sum();
+        let derivative: f64 = (0..4).map(|a| dw[a] * quadratic[idx[a]]).sum();
+
+        // Catmull-Rom reproduces quadratics away from the edges.
+        assert!((value - 0.43 * 0.43).abs() < 1e-12);
+        assert!((derivative - 0.86).abs() < 1e-12);
+        assert!(catmull_rom(&grid, 1.01).is_none());
+    }
+
+    #[test]
+    fn test_interp_uniform() {
+        let values = Array1::from_vec(vec![1.0, 3.0, 4.0]);
+        assert_eq!(interp_uniform(&values, 0.0), 1.0);
+        assert_eq!(interp_uniform(&values, 0.25), 2.0);
+        assert_eq!(interp_uniform(&values, 1.0), 4.0);
+    }
+}
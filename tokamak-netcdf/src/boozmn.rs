@@ -0,0 +1,244 @@
+//! booz_xform output (`boozmn_*.nc`) reader.
+
+use std::f64::consts::TAU;
+use std::path::{Path, PathBuf};
+
+use ndarray::{Array1, Array2, ArrayView1};
+
+use crate::extract::{NcSource, extract_1d_var, extract_2d_var, extract_scalar, optional};
+use crate::metadata::checksum;
+use crate::open::open_file;
+use crate::raw::RawEquilibrium;
+use crate::validate::check_shape;
+use crate::{NcData, NcError, NcSchema, Result};
+
+/// Relative amplitude below which a harmonic is considered zero.
+const NEGLIGIBLE: f64 = 1e-10;
+
+/// Fourier representation of an equilibrium in Boozer coordinates, as written by booz_xform, in
+/// SI units.
+///
+/// Quantities are given on the surfaces of VMEC's half radial grid listed in `jlist`, and each
+/// harmonic varies as cos(mθ - nζ) or sin(mθ - nζ).
+pub struct Boozmn {
+    /// Number of field periods.
+    pub nfp: i32,
+    /// Number of VMEC radial grid points, including the axis.
+    pub ns: usize,
+    /// 1-based radial indices of the surfaces the harmonics are given on.
+    pub jlist: Array1<i32>,
+    /// Poloidal mode numbers m.
+    pub xm: Array1<i32>,
+    /// Toroidal mode numbers n, including the `nfp` factor.
+    pub xn: Array1<i32>,
+    /// Rotational transform ι, on all `ns` radial points.
+    pub iota: Array1<f64>,
+    /// Boozer I, on all `ns` radial points, in \[*T m*\].
+    pub buco: Array1<f64>,
+    /// Boozer G, on all `ns` radial points, in \[*T m*\].
+    pub bvco: Array1<f64>,
+    /// Toroidal flux on VMEC's full radial grid in \[*Wb*\].
+    pub phi: Array1<f64>,
+    /// |B| cosine harmonics in \[*T*\], with shape (surfaces, modes).
+    pub bmnc: Array2<f64>,
+    /// |B| sine harmonics in \[*T*\], present in up-down asymmetric equilibria.
+    pub bmns: Option<Array2<f64>>,
+    /// R cosine harmonics in \[*m*\].
+    pub rmnc: Array2<f64>,
+    /// Z sine harmonics in \[*m*\].
+    pub zmns: Array2<f64>,
+}
+
+impl Boozmn {
+    /// Reads a booz_xform output file.
+    pub fn open(path: &Path) -> Result<Self> {
+        let file = open_file(path)?;
+
+        let f = NcSource::new(&file);
+        let ns = extract_scalar::<i32>(&f, "ns_b")?;
+        let rec = Self {
+            nfp: extract_scalar(&f, "nfp_b")?,
+            ns: usize::try_from(ns).unwrap_or_default(),
+            jlist: extract_1d_var(&f, "jlist")?,
+            xm: extract_1d_var(&f, "ixm_b")?,
+            xn: extract_1d_var(&f, "ixn_b")?,
+            iota: extract_1d_var(&f, "iota_b")?,
+            buco: extract_1d_var(&f, "buco_b")?,
+            bvco: extract_1d_var(&f, "bvco_b")?,
+            phi: extract_1d_var(&f, "phi_b")?,
+            bmnc: extract_2d_var(&f, "bmnc_b")?,
+            bmns: optional(extract_2d_var(&f, "bmns_b"))?,
+            rmnc: extract_2d_var(&f, "rmnc_b")?,
+            zmns: extract_2d_var(&f, "zmns_b")?,
+        };
+        rec.check_shapes()?;
+        Ok(rec)
+    }
+
+    /// Checks that the harmonics and profiles agree on the number of surfaces and modes.
+    fn check_shapes(&self) -> Result<()> {
+        let shape = [self.jlist.len(), self.xm.len()];
+        let harmonics = [
+            ("bmnc_b", Some(&self.bmnc)),
+            ("bmns_b", self.bmns.as_ref()),
+            ("rmnc_b", Some(&self.rmnc)),
+            ("zmns_b", Some(&self.zmns)),
+        ];
+        for (name, arr) in harmonics {
+            if let Some(arr) = arr {
+                check_shape(name, &shape, arr.shape())?;
+            }
+        }
+        check_shape("ixn_b", &shape[1..], self.xn.shape())?;
+        for (name, arr) in [
+            ("iota_b", &self.iota),
+            ("buco_b", &self.buco),
+            ("bvco_b", &self.bvco),
+            ("phi_b", &self.phi),
+        ] {
+            check_shape(name, &[self.ns], arr.shape())?;
+        }
+
+        // Surfaces must lie on the half grid, in increasing order.
+        let in_range = |&j: &i32| (2..=self.ns as i32).contains(&j);
+        let increasing = self.jlist.windows(2).into_iter().all(|w| w[1] > w[0]);
+        match self.jlist.iter().all(in_range) && increasing {
+            true => Ok(()),
+            false => Err(NcError::NonMonotonic("jlist".into())),
+        }
+    }
+
+    /// Returns `true` if all harmonics with n ≠ 0 are negligible.
+    pub fn is_axisymmetric(&self) -> bool {
+        let scale = self.bmnc.iter().fold(0.0_f64, |max, b| max.max(b.abs()));
+        let harmonics = std::iter::once(&self.bmnc).chain(self.bmns.as_ref());
+        harmonics.into_iter().all(|arr| {
+            arr.rows().into_iter().all(|row| {
+                row.iter()
+                    .zip(self.xn.iter())
+                    .all(|(b, &n)| n == 0 || b.abs() <= NEGLIGIBLE * scale)
+            })
+        })
+    }
+
+    /// Reconstructs |B| in \[*T*\] on each surface at the given Boozer `theta` values and
+    /// toroidal angle `zeta`, with shape (surfaces, θ).
+    pub fn b_field(&self, theta: ArrayView1<f64>, zeta: f64) -> Array2<f64> {
+        self.synthesize(Some(&self.bmnc), self.bmns.as_ref(), theta, zeta)
+    }
+
+    /// Sums the cosine and sine harmonics at each (surface, θ) point.
+    fn synthesize(
+        &self,
+        cos: Option<&Array2<f64>>,
+        sin: Option<&Array2<f64>>,
+        theta: ArrayView1<f64>,
+        zeta: f64,
+    ) -> Array2<f64> {
+        Array2::from_shape_fn((self.jlist.len(), theta.len()), |(k, j)| {
+            let angles = self
+                .xm
+                .iter()
+                .zip(self.xn.iter())
+                .map(|(&m, &n)| m as f64 * theta[j] - n as f64 * zeta);
+            angles
+                .enumerate()
+                .map(|(mn, angle)| {
+                    let cos_part = cos.map_or(0.0, |cos| cos[[k, mn]] * angle.cos());
+                    let sin_part = sin.map_or(0.0, |sin| sin[[k, mn]] * angle.sin());
+                    cos_part + sin_part
+                })
+                .sum()
+        })
+    }
+
+    /// Creates an `NcData` on a uniform θ grid of `theta_len` points in \[0, 2π), equivalent to
+    /// opening a NetCDF file with the default [`NcSchema`].
+    ///
+    /// Quantities are normalised by |B| and R of the innermost surface's (0, 0) harmonics, which
+    /// are stored as `baxis` and `raxis`. ψ is the toroidal flux per radian on each surface of
+    /// VMEC's half grid, and signs are dropped, so that ψ, q, I and g are positive.
+    ///
+    /// The returned `NcData`'s path is empty. Fails with [`NcError::NonAxisymmetric`] if any
+    /// harmonic with n ≠ 0 is present, since `NcData` has no toroidal dependence; use
+    /// [`Boozmn::b_field`] for such equilibria.
+    pub fn to_nc_data(&self, theta_len: usize) -> Result<NcData> {
+        if theta_len == 0 || self.jlist.is_empty() {
+            return Err(NcError::InvalidGrid("grid lengths must be positive".into()));
+        }
+        if !self.is_axisymmetric() {
+            return Err(NcError::NonAxisymmetric);
+        }
+
+        // The (0, 0) harmonics of the innermost surface.
+        let Some(mode_00) = (0..self.xm.len()).find(|&mn| self.xm[mn] == 0 && self.xn[mn] == 0)
+        else {
+            return Err(NcError::VariableNotFound("(0, 0) harmonic".into()));
+        };
+        let baxis = self.bmnc[[0, mode_00]].abs();
+        let raxis = self.rmnc[[0, mode_00]].abs();
+        let psi_norm = baxis * raxis * raxis;
+        let current_norm = baxis * raxis;
+
+        // Surface j lies at s = (j - 1.5) / (ns - 1) of VMEC's half grid.
+        // Safe unwrap(); extracted variables are never empty.
+        let psi_edge = self.phi.last().unwrap().abs() / TAU;
+        let half_grid = Array1::from_shape_fn(self.ns, |j| {
+            psi_edge * (j as f64 - 0.5).max(0.0) / (self.ns - 1).max(1) as f64
+        });
+        // Poloidal flux from dψp = ι dψ, with ι constant between the axis and the first point.
+        let mut psip_half = Array1::<f64>::zeros(self.ns);
+        for j in 1..self.ns {
+            let iota = match j {
+                1 => self.iota[1].abs(),
+                _ => 0.5 * (self.iota[j - 1].abs() + self.iota[j].abs()),
+            };
+            psip_half[j] = psip_half[j - 1] + iota * (half_grid[j] - half_grid[j - 1]);
+        }
+
+        let idx: Vec<usize> = self.jlist.iter().map(|&j| j as usize - 1).collect();
+        let on_surfaces = |arr: &Array1<f64>| Array1::from_iter(idx.iter().map(|&j| arr[j].abs()));
+
+        let theta = Array1::from_shape_fn(theta_len, |j| TAU * j as f64 / theta_len as f64);
+        let b = self.b_field(theta.view(), 0.0) / baxis;
+        let r = self.synthesize(Some(&self.rmnc), None, theta.view(), 0.0) / raxis;
+        let z = self.synthesize(None, Some(&self.zmns), theta.view(), 0.0) / raxis;
+
+        RawEquilibrium {
+            baxis,
+            raxis,
+            psi: on_surfaces(&half_grid) / psi_norm,
+            theta,
+            i: on_surfaces(&self.buco) / current_norm,
+            g: on_surfaces(&self.bvco) / current_norm,
+            b,
+            q: Some(on_surfaces(&self.iota).mapv(f64::recip)),
+            psip: Some(on_surfaces(&psip_half) / psi_norm),
+            rz: Some((r, z)),
+        }
+        .into_nc_data(PathBuf::new(), &NcSchema::default())
+    }
+}
+
+impl NcData {
+    /// Creates an NcData from a booz_xform output file of an axisymmetric equilibrium, on a
+    /// uniform θ grid of `theta_len` points. See [`Boozmn::to_nc_data`].
+    pub fn open_boozmn(path: PathBuf, theta_len: usize) -> Result<Self> {
+        let mut rec = Boozmn::open(&path)?.to_nc_data(theta_len)?;
+        rec.metadata.checksum = Some(checksum(&path)?);
+        rec.path = path;
+        Ok(rec)
+    }
+}
+
+impl std::fmt::Debug for Boozmn {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Boozmn")
+            .field("nfp", &self.nfp)
+            .field("ns", &self.ns)
+            .field("surfaces", &self.jlist.len())
+            .field("modes", &self.xm.len())
+            .field("asymmetric", &self.bmns.is_some())
+            .finish()
+    }
+}
@@ -0,0 +1,531 @@
+//! Pure-Rust reader of the NetCDF classic (CDF-1) and 64-bit offset (CDF-2) formats.
+//!
+//! The formats are described in the NetCDF User's Guide, "File Format Specifications". Only
+//! reading is supported.
+
+use std::borrow::Cow;
+use std::path::Path;
+
+use crate::{NcError, Result};
+
+/// Header tags of the dimension, attribute and variable lists.
+mod tag {
+    pub(super) const ABSENT: u32 = 0x00;
+    pub(super) const DIMENSION: u32 = 0x0A;
+    pub(super) const VARIABLE: u32 = 0x0B;
+    pub(super) const ATTRIBUTE: u32 = 0x0C;
+}
+
+/// Number of records of files written in streaming mode, which must be computed from the size.
+const STREAMING: u32 = u32::MAX;
+
+/// External data types of the classic formats.
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum NcType {
+    Byte,
+    Char,
+    Short,
+    Int,
+    Float,
+    Double,
+}
+
+impl NcType {
+    fn from_tag(tag: u32) -> Result<Self> {
+        match tag {
+            1 => Ok(NcType::Byte),
+            2 => Ok(NcType::Char),
+            3 => Ok(NcType::Short),
+            4 => Ok(NcType::Int),
+            5 => Ok(NcType::Float),
+            6 => Ok(NcType::Double),
+            _ => Err(invalid(format!("unknown data type {tag}"))),
+        }
+    }
+
+    /// Size of a single value in bytes.
+    fn size(self) -> usize {
+        match self {
+            NcType::Byte | NcType::Char => 1,
+            NcType::Short => 2,
+            NcType::Int | NcType::Float => 4,
+            NcType::Double => 8,
+        }
+    }
+
+    /// Decodes a single big-endian value as `f64`. Characters are read as their byte value.
+    fn decode(self, bytes: &[u8]) -> f64 {
+        match self {
+            NcType::Byte => f64::from(bytes[0] as i8),
+            NcType::Char => f64::from(bytes[0]),
+            NcType::Short => f64::from(i16::from_be_bytes([bytes[0], bytes[1]])),
+            NcType::Int => f64::from(i32::from_be_bytes(bytes[..4].try_into().unwrap())),
+            NcType::Float => f64::from(f32::from_be_bytes(bytes[..4].try_into().unwrap())),
+            NcType::Double => f64::from_be_bytes(bytes[..8].try_into().unwrap()),
+        }
+    }
+}
+
+/// Value of an attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) enum AttributeValue {
+    Text(String),
+    Bytes(Vec<i8>),
+    Shorts(Vec<i16>),
+    Ints(Vec<i32>),
+    Floats(Vec<f32>),
+    Doubles(Vec<f64>),
+}
+
+/// A named attribute.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Attribute {
+    pub(crate) name: String,
+    pub(crate) value: AttributeValue,
+}
+
+/// A dimension. The record dimension's length is the file's number of records.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Dimension {
+    pub(crate) name: String,
+    pub(crate) len: usize,
+}
+
+/// A variable's header entry.
+#[derive(Debug, Clone, PartialEq)]
+pub(crate) struct Variable {
+    pub(crate) name: String,
+    dimids: Vec<usize>,
+    pub(crate) attributes: Vec<Attribute>,
+    nc_type: NcType,
+    /// Offset of the variable's data, or of its first record.
+    begin: usize,
+}
+
+/// A classic or 64-bit offset NetCDF file, parsed from its contents.
+pub(crate) struct ClassicFile<'b> {
+    bytes: Cow<'b, [u8]>,
+    dimensions: Vec<Dimension>,
+    pub(crate) attributes: Vec<Attribute>,
+    variables: Vec<Variable>,
+    /// Index of the record (unlimited) dimension, if any.
+    record_dim: Option<usize>,
+    /// Size of a record, across all record variables.
+    record_size: usize,
+}
+
+impl ClassicFile<'static> {
+    /// Reads a classic or 64-bit offset NetCDF file.
+    pub(crate) fn open(path: &Path) -> Result<Self> {
+        let bytes = std::fs::read(path).map_err(|source| NcError::ReadError {
+            source,
+            path: path.into(),
+        })?;
+        ClassicFile::parse(Cow::Owned(bytes))
+    }
+}
+
+impl<'b> ClassicFile<'b> {
+    /// Parses the header of a classic or 64-bit offset NetCDF file's contents.
+    pub(crate) fn parse(bytes: Cow<'b, [u8]>) -> Result<Self> {
+        let mut header = Header {
+            bytes: &bytes,
+            position: 0,
+            offset_size: 4,
+        };
+
+        match header.take(4)? {
+            [b'C', b'D', b'F', 1] => header.offset_size = 4,
+            [b'C', b'D', b'F', 2] => header.offset_size = 8,
+            [b'C', b'D', b'F', version] => {
+                return Err(invalid(format!("unsupported format version {version}")));
+            }
+            _ => return Err(invalid("not a classic or 64-bit offset NetCDF file")),
+        }
+        let numrecs = header.u32()?;
+
+        let mut dimensions = Vec::new();
+        for _ in 0..header.list_len(tag::DIMENSION)? {
+            let name = header.name()?;
+            let len = header.u32()? as usize;
+            dimensions.push(Dimension { name, len });
+        }
+        let record_dim = dimensions.iter().position(|dim| dim.len == 0);
+
+        let attributes = header.attributes()?;
+
+        let mut variables = Vec::new();
+        for _ in 0..header.list_len(tag::VARIABLE)? {
+            let name = header.name()?;
+            let ndims = header.u32()? as usize;
+            let dimids = (0..ndims)
+                .map(|_| header.u32().map(|id| id as usize))
+                .collect::<Result<Vec<_>>>()?;
+            if dimids.iter().any(|&id| id >= dimensions.len()) {
+                return Err(invalid(format!("'{name}' has an unknown dimension")));
+            }
+            let attributes = header.attributes()?;
+            let nc_type = NcType::from_tag(header.u32()?)?;
+            let _vsize = header.u32()?;
+            let begin = header.offset()?;
+            variables.push(Variable {
+                name,
+                dimids,
+                attributes,
+                nc_type,
+                begin,
+            });
+        }
+
+        let mut file = Self {
+            bytes: Cow::Borrowed(&[]),
+            dimensions,
+            attributes,
+            variables,
+            record_dim,
+            record_size: 0,
+        };
+        file.record_size = file.record_size()?;
+        let numrecs = match numrecs {
+            STREAMING => file.streaming_numrecs(bytes.len()),
+            numrecs => numrecs as usize,
+        };
+        if let Some(record_dim) = file.record_dim {
+            file.dimensions[record_dim].len = numrecs;
+        }
+        file.bytes = bytes;
+        Ok(file)
+    }
+
+    /// Finds a variable by name.
+    pub(crate) fn variable(&self, name: &str) -> Option<&Variable> {
+        self.variables.iter().find(|var| var.name == name)
+    }
+
+    /// Returns a variable's dimensions.
+    pub(crate) fn dimensions(&self, var: &Variable) -> Vec<&Dimension> {
+        var.dimids.iter().map(|&id| &self.dimensions[id]).collect()
+    }
+
+    /// Reads a variable's values as `f64`. If `index` is given, only that index of the first
+    /// dimension is read.
+    pub(crate) fn values(&self, var: &Variable, index: Option<usize>) -> Result<Vec<f64>> {
+        let dims = self.dimensions(var);
+        let size = var.nc_type.size();
+        let too_large = || invalid(format!("'{}' is too large", var.name));
+        // Number of values per index of the first dimension.
+        let chunk = product(dims.iter().skip(1).map(|dim| dim.len)).ok_or_else(too_large)?;
+        let chunk_size = chunk.checked_mul(size).ok_or_else(too_large)?;
+
+        // Byte range of the `count` values starting `stride` bytes times `k` after the data.
+        let run = |k: usize, stride: usize, count: usize| {
+            let offset = k
+                .checked_mul(stride)
+                .and_then(|offset| offset.checked_add(var.begin))
+                .ok_or_else(too_large)?;
+            let end = count
+                .checked_mul(size)
+                .and_then(|len| len.checked_add(offset))
+                .ok_or_else(too_large)?;
+            match end <= self.bytes.len() {
+                true => Ok(offset..end),
+                false => Err(invalid(format!("'{}' data is truncated", var.name))),
+            }
+        };
+
+        // Contiguous runs of values to read, each checked to lie inside the file.
+        let runs = match (self.is_record(var), index) {
+            (true, Some(index)) => vec![run(index, self.record_size, chunk)?],
+            // Empty records would all be found at the same offset.
+            (true, None) if chunk_size == 0 => Vec::new(),
+            (true, None) => (0..dims[0].len)
+                .map(|record| run(record, self.record_size, chunk))
+                .collect::<Result<_>>()?,
+            (false, Some(index)) => vec![run(index, chunk_size, chunk)?],
+            (false, None) => {
+                let len = product(dims.iter().map(|dim| dim.len)).ok_or_else(too_large)?;
+                vec![run(0, 0, len)?]
+            }
+        };
+
+        let mut values = Vec::with_capacity(runs.iter().map(|run| run.len() / size).sum());
+        for run in runs {
+            let data = &self.bytes[run];
+            values.extend(data.chunks_exact(size).map(|x| var.nc_type.decode(x)));
+        }
+        Ok(values)
+    }
+
+    /// Returns whether a variable's first dimension is the record dimension.
+    fn is_record(&self, var: &Variable) -> bool {
+        self.record_dim.is_some() && var.dimids.first().copied() == self.record_dim
+    }
+
+    /// Size of a single record of a variable, padded to 4 bytes.
+    fn record_var_size(&self, var: &Variable) -> Result<usize> {
+        product(self.dimensions(var).iter().skip(1).map(|dim| dim.len))
+            .and_then(|len| len.checked_mul(var.nc_type.size()))
+            .ok_or_else(|| invalid(format!("a record of '{}' is too large", var.name)))
+    }
+
+    /// Calculates the size of a record. Record variables are padded to 4 bytes, unless there is
+    /// only one.
+    fn record_size(&self) -> Result<usize> {
+        let sizes = self
+            .variables
+            .iter()
+            .filter(|var| self.is_record(var))
+            .map(|var| self.record_var_size(var))
+            .collect::<Result<Vec<usize>>>()?;
+        match sizes.as_slice() {
+            [size] => Ok(*size),
+            _ => sizes
+                .iter()
+                .try_fold(0usize, |total, size| {
+                    total.checked_add(size.checked_next_multiple_of(4)?)
+                })
+                .ok_or_else(|| invalid("records are too large")),
+        }
+    }
+
+    /// Calculates the number of records of a file written in streaming mode from its size.
+    fn streaming_numrecs(&self, file_len: usize) -> usize {
+        let first = self
+            .variables
+            .iter()
+            .filter(|var| self.is_record(var))
+            .map(|var| var.begin)
+            .min();
+        match (first, self.record_size) {
+            (Some(begin), 1..) => file_len.saturating_sub(begin) / self.record_size,
+            _ => 0,
+        }
+    }
+}
+
+/// Cursor over a file's header.
+struct Header<'a> {
+    bytes: &'a [u8],
+    position: usize,
+    /// Size of data offsets: 4 bytes in the classic format, 8 in the 64-bit offset format.
+    offset_size: usize,
+}
+
+impl<'a> Header<'a> {
+    fn take(&mut self, len: usize) -> Result<&'a [u8]> {
+        let end = self
+            .position
+            .checked_add(len)
+            .filter(|&end| end <= self.bytes.len())
+            .ok_or_else(|| invalid("header is truncated"))?;
+        let taken = &self.bytes[self.position..end];
+        self.position = end;
+        Ok(taken)
+    }
+
+    /// Takes `len` bytes, followed by padding to a multiple of 4 bytes.
+    fn take_padded(&mut self, len: usize) -> Result<&'a [u8]> {
+        let taken = self.take(len)?;
+        self.take(len.next_multiple_of(4) - len)?;
+        Ok(taken)
+    }
+
+    fn u32(&mut self) -> Result<u32> {
+        Ok(u32::from_be_bytes(self.take(4)?.try_into().unwrap()))
+    }
+
+    fn offset(&mut self) -> Result<usize> {
+        let offset = match self.offset_size {
+            4 => u64::from(self.u32()?),
+            _ => u64::from_be_bytes(self.take(8)?.try_into().unwrap()),
+        };
+        usize::try_from(offset).map_err(|_| invalid("data offset is too large"))
+    }
+
+    fn name(&mut self) -> Result<String> {
+        let len = self.u32()? as usize;
+        let bytes = self.take_padded(len)?;
+        String::from_utf8(bytes.to_vec()).map_err(|_| invalid("name is not valid UTF-8"))
+    }
+
+    /// Reads the length of a dimension, attribute or variable list with the given tag.
+    fn list_len(&mut self, expected: u32) -> Result<usize> {
+        let tag = self.u32()?;
+        let len = self.u32()? as usize;
+        match tag {
+            tag::ABSENT if len == 0 => Ok(0),
+            tag if tag == expected => Ok(len),
+            _ => Err(invalid(format!("unexpected header tag {tag:#x}"))),
+        }
+    }
+
+    fn attributes(&mut self) -> Result<Vec<Attribute>> {
+        let mut attributes = Vec::new();
+        for _ in 0..self.list_len(tag::ATTRIBUTE)? {
+            let name = self.name()?;
+            let nc_type = NcType::from_tag(self.u32()?)?;
+            let len = self.u32()? as usize;
+            let size = nc_type.size();
+            let len = len
+                .checked_mul(size)
+                .ok_or_else(|| invalid(format!("attribute '{name}' is too large")))?;
+            let bytes = self.take_padded(len)?;
+            let values = bytes.chunks_exact(size);
+
+            let value = match nc_type {
+                NcType::Char => {
+                    let text = String::from_utf8_lossy(bytes);
+                    AttributeValue::Text(text.trim_end_matches('\0').into())
+                }
+                NcType::Byte => AttributeValue::Bytes(bytes.iter().map(|&x| x as i8).collect()),
+                NcType::Short => AttributeValue::Shorts(
+                    values
+                        .map(|x| i16::from_be_bytes(x.try_into().unwrap()))
+                        .collect(),
+                ),
+                NcType::Int => AttributeValue::Ints(
+                    values
+                        .map(|x| i32::from_be_bytes(x.try_into().unwrap()))
+                        .collect(),
+                ),
+                NcType::Float => AttributeValue::Floats(
+                    values
+                        .map(|x| f32::from_be_bytes(x.try_into().unwrap()))
+                        .collect(),
+                ),
+                NcType::Double => AttributeValue::Doubles(
+                    values
+                        .map(|x| f64::from_be_bytes(x.try_into().unwrap()))
+                        .collect(),
+                ),
+            };
+            attributes.push(Attribute { name, value });
+        }
+        Ok(attributes)
+    }
+}
+
+/// Multiplies dimension lengths, returning `None` on overflow.
+fn product(mut lens: impl Iterator<Item = usize>) -> Option<usize> {
+    lens.try_fold(1usize, usize::checked_mul)
+}
+
+fn invalid(reason: impl Into<Box<str>>) -> NcError {
+    NcError::ClassicFormat(reason.into())
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use netcdf::Options;
+
+    #[test]
+    fn test_records() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("records_{}.nc", std::process::id()));
+        {
+            let mut f = netcdf::create_with(&path, Options::CLASSIC).unwrap();
+            f.add_unlimited_dimension("time").unwrap();
+            f.add_dimension("x", 3).unwrap();
+            f.add_variable::<f64>("a", &["time", "x"]).unwrap();
+            // A record of `b` is 6 bytes, padded to 8.
+            f.add_variable::<i16>("b", &["time", "x"]).unwrap();
+            f.enddef().unwrap();
+            let values: Vec<f64> = (0..6).map(f64::from).collect();
+            f.variable_mut("a")
+                .unwrap()
+                .put_values(&values, (..2, ..))
+                .unwrap();
+            f.variable_mut("b")
+                .unwrap()
+                .put_values(&[1i16, 2, 3, 4, 5, 6], (..2, ..))
+                .unwrap();
+        }
+
+        let file = ClassicFile::open(&path)?;
+        std::fs::remove_file(&path).unwrap();
+        let (a, b) = (file.variable("a").unwrap(), file.variable("b").unwrap());
+        assert_eq!(file.dimensions(a)[0].len, 2);
+        assert_eq!(file.values(a, None)?, [0.0, 1.0, 2.0, 3.0, 4.0, 5.0]);
+        assert_eq!(file.values(a, Some(1))?, [3.0, 4.0, 5.0]);
+        assert_eq!(file.values(b, Some(1))?, [4.0, 5.0, 6.0]);
+        Ok(())
+    }
+
+    #[test]
+    fn test_invalid() {
+        fn parse(bytes: &[u8]) -> Result<ClassicFile<'_>> {
+            ClassicFile::parse(Cow::Borrowed(bytes))
+        }
+        assert!(matches!(
+            parse(b"\x89HDF\r\n"),
+            Err(NcError::ClassicFormat(_))
+        ));
+        assert!(matches!(parse(b"CDF\x01"), Err(NcError::ClassicFormat(_))));
+        assert!(matches!(
+            parse(b"CDF\x05\0\0\0\0"),
+            Err(NcError::ClassicFormat(_))
+        ));
+    }
+
+    /// Builds a classic file with one dimension per length in `dims`, 0 for the record
+    /// dimension, and a `double` variable `v` over all of them, whose data follows the header.
+    fn header(numrecs: u32, dims: &[u32]) -> Vec<u8> {
+        let word = |x: u32| x.to_be_bytes();
+        let mut bytes = b"CDF\x01".to_vec();
+        bytes.extend(word(numrecs));
+        bytes.extend(word(tag::DIMENSION));
+        bytes.extend(word(dims.len() as u32));
+        for (k, &len) in dims.iter().enumerate() {
+            bytes.extend(word(1));
+            bytes.extend([b'a' + k as u8, 0, 0, 0]);
+            bytes.extend(word(len));
+        }
+        bytes.extend([0; 8]);
+        bytes.extend(word(tag::VARIABLE));
+        bytes.extend(word(1));
+        bytes.extend(word(1));
+        bytes.extend(*b"v\0\0\0");
+        bytes.extend(word(dims.len() as u32));
+        for k in 0..dims.len() {
+            bytes.extend(word(k as u32));
+        }
+        bytes.extend([0; 8]);
+        bytes.extend(word(6));
+        bytes.extend(word(0));
+        let begin = bytes.len() as u32 + 4;
+        bytes.extend(word(begin));
+        bytes.extend(1.5f64.to_be_bytes());
+        bytes
+    }
+
+    #[test]
+    fn test_malformed_sizes() -> Result<()> {
+        let values = |bytes: &[u8], index| {
+            let file = ClassicFile::parse(Cow::Borrowed(bytes))?;
+            file.values(file.variable("v").unwrap(), index)
+        };
+        assert_eq!(values(&header(0, &[1]), None)?, [1.5]);
+
+        // Lengths whose product overflows, and data beyond the end of the file.
+        let max = u32::MAX;
+        for (numrecs, dims, index) in [
+            (0, vec![max, max, max], None),
+            (0, vec![2, max, max], Some(1)),
+            (0, vec![max, max], Some(max as usize)),
+            (0, vec![1_000_000_000], None),
+            (0, vec![1], Some(usize::MAX)),
+            (max - 1, vec![0, 1000], None),
+            (3, vec![0, 2], Some(usize::MAX)),
+            (0, vec![0, max, max, max], None),
+        ] {
+            assert!(
+                matches!(
+                    values(&header(numrecs, &dims), index),
+                    Err(NcError::ClassicFormat(_))
+                ),
+                "dims = {dims:?}, index = {index:?}"
+            );
+        }
+        Ok(())
+    }
+}
@@ -3,8 +3,7 @@
 use ndarray::Array1;
 
 use crate::{
-    Result,
-    extract::{extract_1d_var, extract_var_with_axis_value},
+    NcSchema, Result, axis::prepend_axis_value, extract::NcSource, extract::extract_1d_var,
 };
 
 /// Representation of the equilibrium's `psi` and `boozer_theta` coordinates.
@@ -21,31 +20,49 @@ pub struct Coords {
     pub psi_span: (f64, f64),
     /// The θ coordinate's span (min, max).
     pub theta_span: (f64, f64),
+    /// Whether `psi` starts with an extrapolated value on the magnetic axis, which is not part of
+    /// the file.
+    pub extrapolated_axis: bool,
 }
 
 impl Coords {
-    /// Creates a `Coords` containing the coordinate variables from the NetCDF file.
-    pub(crate) fn build(f: &netcdf::File) -> Result<Self> {
+    /// Creates a `Coords` containing the coordinate variables from the NetCDF file. Also returns
+    /// the file's ψ values, without the axis value, which the other quantities are extrapolated
+    /// from.
+    pub(crate) fn build(f: &NcSource, schema: &NcSchema) -> Result<(Self, Array1<f64>)> {
         // Extrapolate psi to later extrapolate all other variables to include a value
         // at the axis.
-        let psi: Array1<f64> = extract_var_with_axis_value(f, "psi", 0.0)?;
-        let theta = extract_1d_var(f, "boozer_theta")?;
+        let file_psi: Array1<f64> = extract_1d_var(f, &schema.psi)?;
+        let psi = prepend_axis_value(
+            &schema.psi,
+            file_psi.view(),
+            file_psi.clone(),
+            schema.axis.psi,
+        )?;
+        let theta = extract_1d_var(f, &schema.theta)?;
+        let extrapolated_axis = psi.len() > file_psi.len();
+
+        Ok((Self::new(psi, theta, extrapolated_axis), file_psi))
+    }
 
+    /// Creates a `Coords` from the ψ and θ coordinates. Both must be non-empty.
+    pub(crate) fn new(psi: Array1<f64>, theta: Array1<f64>, extrapolated_axis: bool) -> Self {
         let psi_len = psi.len();
         let theta_len = theta.len();
 
-        // Safe unwrap(); both psi and theta have already been checked.
+        // Safe unwrap(); both psi and theta are non-empty.
         let psi_span = (*psi.first().unwrap(), *psi.last().unwrap());
         let theta_span = (*theta.first().unwrap(), *theta.last().unwrap());
 
-        Ok(Coords {
+        Coords {
             psi,
             theta,
             psi_len,
             theta_len,
             psi_span,
             theta_span,
-        })
+            extrapolated_axis,
+        }
     }
 }
 
@@ -1,10 +1,12 @@
 //! `Currents` implementation.
 
-use ndarray::Array1;
+use ndarray::{Array1, ArrayView1};
 
 use crate::{
-    Result,
-    extract::{extract_var_with_axis_value, extract_var_with_first_axis_value},
+    Accelerator, Cubic, DomainPolicy, Interpolation, NcData, NcError, NcSchema, Result,
+    extract::{NcSource, extract_1d_var_with_axis},
+    spline::Hermite,
+    validate::check_shape,
 };
 
 /// Representation of the equilibrium's **I** and **g** toroidal and poloidal plasma currents.
@@ -21,31 +23,195 @@ pub struct Currents {
     i_span: (f64, f64),
     /// The poloidal current's span (min, max).
     g_span: (f64, f64),
+    /// Natural cubic splines of **I** and **g** over ψ.
+    spline: CurrentsSpline,
+}
+
+/// Interpolation of **I** and **g** over the ψ coordinate of an equilibrium, with an
+/// [`Interpolation`] scheme.
+#[derive(Clone)]
+pub struct CurrentsSpline {
+    /// Name of the ψ variable, for errors.
+    psi_name: Box<str>,
+    i: Hermite,
+    g: Hermite,
+    domain: DomainPolicy,
 }
 
 impl Currents {
     /// Creates a `Currents` containing the plasma currents **I** and **g** from the NetCDF file.
-    pub(crate) fn build(f: &netcdf::File) -> Result<Self> {
-        let g = extract_var_with_first_axis_value(f, "g_norm")?;
-        let i = extract_var_with_axis_value(f, "I_norm", 0.0)?;
+    /// `coord_psi` is the ψ coordinate, including its axis value if extrapolated, and `file_psi`
+    /// the file's ψ values.
+    pub(crate) fn build(
+        f: &NcSource,
+        schema: &NcSchema,
+        coord_psi: ArrayView1<f64>,
+        file_psi: ArrayView1<f64>,
+    ) -> Result<Self> {
+        let g = extract_1d_var_with_axis(f, &schema.g, file_psi, schema.axis.g)?;
+        let i = extract_1d_var_with_axis(f, &schema.i, file_psi, schema.axis.i)?;
+
+        Self::new(coord_psi, i, g, schema)
+    }
 
+    /// Creates a `Currents` from the **I** and **g** values on the ψ coordinate `psi`, which must
+    /// be non-empty and of the same length. Errors name the currents' variables after `schema`.
+    pub(crate) fn new(
+        psi: ArrayView1<f64>,
+        i: Array1<f64>,
+        g: Array1<f64>,
+        schema: &NcSchema,
+    ) -> Result<Self> {
+        let spline = CurrentsSpline::new(psi, &i, &g, schema, &Cubic)?;
         let g_len = g.len();
         let i_len = i.len();
 
-        // Safe unwrap(); both g and i have already been checked.
+        // Safe unwrap(); both g and i have the length of the non-empty ψ.
         let g_span = (*g.first().unwrap(), *g.last().unwrap());
         let i_span = (*i.first().unwrap(), *i.last().unwrap());
 
-        let currents = Currents {
+        Ok(Currents {
             i,
             g,
             i_len,
             g_len,
             i_span,
             g_span,
+            spline,
+        })
+    }
+
+    /// Evaluates **I**, **g** and their derivatives with respect to ψ at `psi`, returning
+    /// (I, g, dI/dψ, dg/dψ).
+    ///
+    /// The values are interpolated by natural cubic splines over the ψ coordinate, including
+    /// the axis value if extrapolated, built when the `Currents` is created. The splines pass
+    /// through every knot, and their second derivatives vanish on the first and last ones.
+    /// Outside the coordinate's span, the [`DomainPolicy`] set by [`Currents::with_domain`]
+    /// applies. Other interpolation schemes are available with [`NcData::currents_spline_with`].
+    ///
+    /// ```
+    /// # use tokamak_netcdf::{CircularTokamak, NcError};
+    /// # fn main() -> Result<(), NcError> {
+    /// let nc_data = CircularTokamak::default().to_nc_data()?;
+    /// let (i, g, di, dg) = nc_data.currents.eval(0.5 * nc_data.scalars.psi_wall)?;
+    /// assert!(i > 0.0 && di > 0.0);
+    /// assert!((g - 1.0).abs() < 1e-12 && dg.abs() < 1e-12);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn eval(&self, psi: f64) -> Result<(f64, f64, f64, f64)> {
+        self.spline.eval(psi)
+    }
+
+    /// Sets the behaviour of [`Currents::eval`] outside the ψ coordinate's span, which is
+    /// [`DomainPolicy::Extend`] by default, extending the first or last cubic piece.
+    pub fn with_domain(mut self, policy: DomainPolicy) -> Self {
+        self.spline.domain = policy;
+        self
+    }
+
+    /// Evaluates **I**, **g** and their derivatives at `psi` like [`Currents::eval`], starting
+    /// the search for `psi`'s cell from the last one of `acc`.
+    pub fn eval_with(&self, psi: f64, acc: &mut Accelerator) -> Result<(f64, f64, f64, f64)> {
+        self.spline.eval_with(psi, acc)
+    }
+}
+
+impl NcData {
+    /// Creates an interpolation of **I** and **g** over the equilibrium's ψ coordinate,
+    /// including the axis value if extrapolated, with the interpolation `scheme`.
+    ///
+    /// Every scheme passes through the same values on the ψ coordinate.
+    ///
+    /// ```
+    /// # use tokamak_netcdf::{CircularTokamak, NcError, Steffen};
+    /// # fn main() -> Result<(), NcError> {
+    /// let nc_data = CircularTokamak::default().to_nc_data()?;
+    /// let spline = nc_data.currents_spline_with(&Steffen)?;
+    /// let psi = nc_data.coords.psi[3];
+    /// assert_eq!(spline.eval(psi)?.0, nc_data.currents.i[3]);
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn currents_spline_with<I>(&self, scheme: &I) -> Result<CurrentsSpline>
+    where
+        I: Interpolation + ?Sized,
+    {
+        let currents = &self.currents;
+        CurrentsSpline::new(
+            self.coords.psi.view(),
+            &currents.i,
+            &currents.g,
+            &self.schema,
+            scheme,
+        )
+    }
+}
+
+impl CurrentsSpline {
+    /// Creates an interpolation of **I** and **g** over `psi` with the interpolation `scheme`.
+    /// Errors name the currents' variables after `schema`.
+    pub(crate) fn new<I>(
+        psi: ArrayView1<f64>,
+        i: &Array1<f64>,
+        g: &Array1<f64>,
+        schema: &NcSchema,
+        scheme: &I,
+    ) -> Result<Self>
+    where
+        I: Interpolation + ?Sized,
+    {
+        let psi = psi.to_vec();
+        if psi.is_empty() {
+            return Err(NcError::InvalidGrid(
+                "interpolation needs at least 1 psi point".into(),
+            ));
+        }
+        let interpolate = |name: &str, values: &Array1<f64>| {
+            check_shape(name, &[psi.len()], values.shape())?;
+            Ok(Hermite::new(&psi, &values.to_vec(), scheme))
         };
+        Ok(CurrentsSpline {
+            psi_name: schema.psi.clone(),
+            i: interpolate(&schema.i, i)?,
+            g: interpolate(&schema.g, g)?,
+            domain: DomainPolicy::Extend,
+        })
+    }
+
+    /// Sets the behaviour outside the ψ coordinate's span, which is [`DomainPolicy::Extend`] by
+    /// default.
+    pub fn with_domain(mut self, policy: DomainPolicy) -> Self {
+        self.domain = policy;
+        self
+    }
 
-        Ok(currents)
+    /// Evaluates **I**, **g** and their derivatives with respect to ψ at `psi`, returning
+    /// (I, g, dI/dψ, dg/dψ). Outside the ψ coordinate's span, the spline's [`DomainPolicy`]
+    /// applies.
+    pub fn eval(&self, psi: f64) -> Result<(f64, f64, f64, f64)> {
+        self.eval_with(psi, &mut Accelerator::new())
+    }
+
+    /// Evaluates **I**, **g** and their derivatives at `psi` like [`CurrentsSpline::eval`],
+    /// starting the search for `psi`'s cell from the last one of `acc`.
+    pub fn eval_with(&self, psi: f64, acc: &mut Accelerator) -> Result<(f64, f64, f64, f64)> {
+        let (psi, vacuum) = self.domain.apply(&self.psi_name, psi, self.i.span())?;
+        let (i, di) = self.i.eval(psi, &mut acc.psi);
+        let (g, dg) = self.g.eval(psi, &mut acc.psi);
+        Ok(match vacuum {
+            true => (i, g, 0.0, 0.0),
+            false => (i, g, di, dg),
+        })
+    }
+}
+
+impl std::fmt::Debug for CurrentsSpline {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("CurrentsSpline")
+            .field("domain", &self.domain)
+            .finish_non_exhaustive()
     }
 }
 
@@ -0,0 +1,73 @@
+//! Evaluation outside the equilibrium's ψ span.
+
+use crate::{NcError, Result};
+
+/// Behaviour of the evaluators outside the span of the ψ coordinate, e.g. for particles that
+/// cross `Scalars::psi_wall`. Only ψ is concerned, since θ is periodic.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum DomainPolicy {
+    /// Extends the first or last piece of the interpolation.
+    #[default]
+    Extend,
+    /// Fails with [`NcError::OutOfDomain`], e.g. to detect lost particles.
+    Error,
+    /// Evaluates on the closest end of the span, including the derivatives.
+    Clamp,
+    /// Vacuum-like model beyond the last surface: without plasma currents, the quantities keep
+    /// their values on the last surface, and their ψ derivatives vanish. B keeps the θ variation
+    /// of the last surface. Before the first surface, same as `Clamp`.
+    Vacuum,
+}
+
+impl DomainPolicy {
+    /// Returns the ψ to evaluate at instead of `psi`, and whether the ψ derivatives vanish
+    /// there, or the error naming the ψ variable `name` if `psi` is outside `span` and must not
+    /// be evaluated.
+    pub(crate) fn apply(self, name: &str, psi: f64, span: (f64, f64)) -> Result<(f64, bool)> {
+        let (first, last) = span;
+        if (first..=last).contains(&psi) {
+            return Ok((psi, false));
+        }
+        match self {
+            Self::Extend => Ok((psi, false)),
+            Self::Error => Err(NcError::OutOfDomain {
+                name: name.into(),
+                value: psi,
+                span,
+            }),
+            Self::Clamp => Ok((psi.clamp(first, last), false)),
+            Self::Vacuum if psi > last => Ok((last, true)),
+            Self::Vacuum => Ok((psi.clamp(first, last), false)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_apply() -> Result<()> {
+        use DomainPolicy::*;
+        let span = (0.0, 2.0);
+        for policy in [Extend, Error, Clamp, Vacuum] {
+            assert_eq!(policy.apply("psi", 1.5, span)?, (1.5, false));
+            assert_eq!(policy.apply("psi", 2.0, span)?, (2.0, false));
+        }
+        assert_eq!(Extend.apply("psi", 3.0, span)?, (3.0, false));
+        assert_eq!(Clamp.apply("psi", 3.0, span)?, (2.0, false));
+        assert_eq!(Clamp.apply("psi", -1.0, span)?, (0.0, false));
+        assert_eq!(Vacuum.apply("psi", 3.0, span)?, (2.0, true));
+        assert_eq!(Vacuum.apply("psi", -1.0, span)?, (0.0, false));
+        assert!(matches!(
+            Error.apply("psi", -1.0, span),
+            Err(NcError::OutOfDomain { value: -1.0, .. })
+        ));
+        assert!(matches!(
+            Error.apply("psi_tor", 3.0, span),
+            Err(NcError::OutOfDomain { name, .. }) if &*name == "psi_tor"
+        ));
+        assert!(Error.apply("psi", f64::NAN, span).is_err());
+        Ok(())
+    }
+}
@@ -0,0 +1,340 @@
+//! EQDSK G-file reader.
+
+use std::path::{Path, PathBuf};
+
+use ndarray::{Array1, Array2};
+
+use crate::boozer::{BoozerGrid, FluxMap};
+use crate::metadata::checksum;
+use crate::{AttributeValue, NcData, NcError, NcSchema, Result};
+
+/// Contents of an EQDSK G-file, in SI units.
+///
+/// The poloidal flux is per radian, and the 1D profiles are given on a uniform poloidal flux grid
+/// from the axis (`simag`) to the boundary (`sibry`).
+pub struct Eqdsk {
+    /// Free-form description in the file's header.
+    pub description: Box<str>,
+    /// Number of R grid points.
+    pub nw: usize,
+    /// Number of Z grid points.
+    pub nh: usize,
+    /// Width of the (R, Z) grid in \[*m*\].
+    pub rdim: f64,
+    /// Height of the (R, Z) grid in \[*m*\].
+    pub zdim: f64,
+    /// Major radius where `bcentr` is given, in \[*m*\].
+    pub rcentr: f64,
+    /// Smallest R of the grid in \[*m*\].
+    pub rleft: f64,
+    /// Z at the middle of the grid in \[*m*\].
+    pub zmid: f64,
+    /// Magnetic axis' R in \[*m*\].
+    pub rmaxis: f64,
+    /// Magnetic axis' Z in \[*m*\].
+    pub zmaxis: f64,
+    /// Poloidal flux on the axis in \[*Wb/rad*\].
+    pub simag: f64,
+    /// Poloidal flux on the boundary in \[*Wb/rad*\].
+    pub sibry: f64,
+    /// Vacuum toroidal field at `rcentr` in \[*T*\].
+    pub bcentr: f64,
+    /// Plasma current in \[*A*\].
+    pub current: f64,
+    /// Poloidal current function F = R Bφ in \[*T m*\].
+    pub fpol: Array1<f64>,
+    /// Plasma pressure in \[*Pa*\].
+    pub pres: Array1<f64>,
+    /// FF' in \[*T² m² / (Wb/rad)*\].
+    pub ffprim: Array1<f64>,
+    /// P' in \[*Pa / (Wb/rad)*\].
+    pub pprime: Array1<f64>,
+    /// Poloidal flux on the (R, Z) grid in \[*Wb/rad*\], with shape (Z, R).
+    pub psirz: Array2<f64>,
+    /// Safety factor.
+    pub qpsi: Array1<f64>,
+    /// Plasma boundary (R, Z) points in \[*m*\]. Empty if the file does not contain it.
+    pub boundary: Vec<[f64; 2]>,
+    /// Limiter (R, Z) points in \[*m*\]. Empty if the file does not contain it.
+    pub limiter: Vec<[f64; 2]>,
+}
+
+impl Eqdsk {
+    /// Reads and parses a G-file.
+    pub fn read(path: &Path) -> Result<Self> {
+        if !path.exists() {
+            return Err(NcError::FileNotFound(path.into()));
+        }
+        let text = std::fs::read_to_string(path).map_err(|source| NcError::ReadError {
+            source,
+            path: path.into(),
+        })?;
+        Self::parse(&text)
+    }
+
+    /// Parses the contents of a G-file.
+    ///
+    /// Numbers may be written without separating whitespace, as Fortran's `5e16.9` format does
+    /// with negative values.
+    pub fn parse(text: &str) -> Result<Self> {
+        let (header, body) = text.split_once('\n').unwrap_or((text, ""));
+
+        // The grid dimensions are the last 2 integers of the header.
+        let mut dims = header.split_whitespace().rev().map(str::parse::<usize>);
+        let (nh, nw) = match (dims.next(), dims.next()) {
+            (Some(Ok(nh)), Some(Ok(nw))) if nw >= 2 && nh >= 2 => (nh, nw),
+            _ => return Err(parse_error("header does not end with the grid dimensions")),
+        };
+        let description = header.get(..48).unwrap_or("").trim().into();
+
+        let mut values = Values::new(numbers(body)?);
+        let [rdim, zdim, rcentr, rleft, zmid] = values.take_array("grid")?;
+        let [rmaxis, zmaxis, simag, sibry, bcentr] = values.take_array("axis")?;
+        let [current, _, _, _, _] = values.take_array("current")?;
+        let [_, _, _, _, _] = values.take_array("header")?;
+
+        let fpol = values.take(nw, "fpol")?;
+        let pres = values.take(nw, "pres")?;
+        let ffprim = values.take(nw, "ffprim")?;
+        let pprime = values.take(nw, "pprime")?;
+        let len = nw
+            .checked_mul(nh)
+            .ok_or_else(|| parse_error("grid dimensions are too large"))?;
+        let psirz = values.take(len, "psirz")?;
+        // Safe unwrap(); the length matches the shape.
+        let psirz = psirz.into_shape_with_order((nh, nw)).unwrap();
+        let qpsi = values.take(nw, "qpsi")?;
+
+        // Some codes omit the boundary and limiter.
+        let (boundary, limiter) = match values.remaining() {
+            0 => (Vec::new(), Vec::new()),
+            _ => {
+                let nbbbs = values.take_count("nbbbs")?;
+                let limitr = values.take_count("limitr")?;
+                (
+                    values.take_points(nbbbs, "boundary")?,
+                    values.take_points(limitr, "limiter")?,
+                )
+            }
+        };
+
+        Ok(Self {
+            description,
+            nw,
+            nh,
+            rdim,
+            zdim,
+            rcentr,
+            rleft,
+            zmid,
+            rmaxis,
+            zmaxis,
+            simag,
+            sibry,
+            bcentr,
+            current,
+            fpol,
+            pres,
+            ffprim,
+            pprime,
+            psirz,
+            qpsi,
+            boundary,
+            limiter,
+        })
+    }
+
+    /// Maps the equilibrium to Boozer coordinates on `grid`, creating an `NcData` equivalent to
+    /// opening a NetCDF file with the default [`NcSchema`].
+    ///
+    /// Quantities are normalised by the field strength and the major radius of the magnetic
+    /// axis, which are stored as `baxis` and `raxis`. Signs are dropped, so that ψ, q, I and g
+    /// are positive regardless of the file's COCOS convention.
+    ///
+    /// The returned `NcData`'s path is empty.
+    pub fn to_nc_data(&self, grid: &BoozerGrid) -> Result<NcData> {
+        let z0 = self.zmid - 0.5 * self.zdim;
+        let flux_map = FluxMap {
+            r: Array1::linspace(self.rleft, self.rleft + self.rdim, self.nw),
+            z: Array1::linspace(z0, z0 + self.zdim, self.nh),
+            psi: self.psirz.clone(),
+            axis: (self.rmaxis, self.zmaxis),
+            psi_axis: self.simag,
+            psi_boundary: self.sibry,
+            f: self.fpol.clone(),
+            q: self.qpsi.clone(),
+        };
+        flux_map
+            .to_raw(grid)?
+            .into_nc_data(PathBuf::new(), &NcSchema::default())
+    }
+}
+
+impl NcData {
+    /// Creates an NcData from an EQDSK G-file, by mapping the equilibrium to Boozer coordinates
+    /// on `grid`. See [`Eqdsk::to_nc_data`].
+    pub fn open_eqdsk(path: PathBuf, grid: &BoozerGrid) -> Result<Self> {
+        let eqdsk = Eqdsk::read(&path)?;
+        let mut rec = eqdsk.to_nc_data(grid)?;
+        rec.metadata.checksum = Some(checksum(&path)?);
+        rec.metadata.attributes.insert(
+            "description".into(),
+            AttributeValue::Str(eqdsk.description.into()),
+        );
+        rec.path = path;
+        Ok(rec)
+    }
+}
+
+impl std::fmt::Debug for Eqdsk {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Eqdsk")
+            .field("description", &self.description)
+            .field("nw", &self.nw)
+            .field("nh", &self.nh)
+            .field("rmaxis", &self.rmaxis)
+            .field("zmaxis", &self.zmaxis)
+            .field("simag", &self.simag)
+            .field("sibry", &self.sibry)
+            .field("bcentr", &self.bcentr)
+            .field("current", &self.current)
+            .field("boundary_len", &self.boundary.len())
+            .field("limiter_len", &self.limiter.len())
+            .finish()
+    }
+}
+
+fn parse_error(reason: &str) -> NcError {
+    NcError::EqdskParse(reason.into())
+}
+
+/// Splits `text` into numbers, including ones not separated by whitespace, such as
+/// "1.0E+00-2.0E+00". Fortran's `D` exponent marker is accepted.
+fn numbers(text: &str) -> Result<Vec<f64>> {
+    let bytes = text.as_bytes();
+    let digits_from = |mut pos: usize| {
+        while pos < bytes.len() && (bytes[pos].is_ascii_digit() || bytes[pos] == b'.') {
+            pos += 1;
+        }
+        pos
+    };
+
+    let mut values = Vec::new();
+    let mut pos = 0;
+    while pos < bytes.len() {
+        if bytes[pos].is_ascii_whitespace() {
+            pos += 1;
+            continue;
+        }
+
+        let start = pos;
+        if matches!(bytes[pos], b'+' | b'-') {
+            pos += 1;
+        }
+        pos = digits_from(pos);
+        if pos < bytes.len() && matches!(bytes[pos], b'e' | b'E' | b'd' | b'D') {
+            pos += 1;
+            if pos < bytes.len() && matches!(bytes[pos], b'+' | b'-') {
+                pos += 1;
+            }
+            pos = digits_from(pos);
+        }
+
+        let token = text[start..pos].replace(['d', 'D'], "e");
+        match token.parse::<f64>() {
+            Ok(value) => values.push(value),
+            Err(_) => {
+                let found: String = text[start..].chars().take(16).collect();
+                return Err(parse_error(&format!("invalid number at '{found}'")));
+            }
+        }
+    }
+    Ok(values)
+}
+
+/// Sequential access to the file's numbers.
+struct Values {
+    values: Vec<f64>,
+    pos: usize,
+}
+
+impl Values {
+    fn new(values: Vec<f64>) -> Self {
+        Self { values, pos: 0 }
+    }
+
+    fn remaining(&self) -> usize {
+        self.values.len() - self.pos
+    }
+
+    fn take_slice(&mut self, n: usize, name: &str) -> Result<&[f64]> {
+        if self.remaining() < n {
+            return Err(parse_error(&format!(
+                "expected {n} values for '{name}', found {}",
+                self.remaining()
+            )));
+        }
+        self.pos += n;
+        Ok(&self.values[self.pos - n..self.pos])
+    }
+
+    fn take(&mut self, n: usize, name: &str) -> Result<Array1<f64>> {
+        Ok(Array1::from_vec(self.take_slice(n, name)?.to_vec()))
+    }
+
+    fn take_array<const N: usize>(&mut self, name: &str) -> Result<[f64; N]> {
+        // Safe unwrap(); the slice has exactly N values.
+        Ok(self.take_slice(N, name)?.try_into().unwrap())
+    }
+
+    fn take_count(&mut self, name: &str) -> Result<usize> {
+        let [value] = self.take_array(name)?;
+        match value >= 0.0 && value.fract() == 0.0 {
+            true => Ok(value as usize),
+            false => Err(parse_error(&format!("'{name}' is not a valid count"))),
+        }
+    }
+
+    fn take_points(&mut self, n: usize, name: &str) -> Result<Vec<[f64; 2]>> {
+        let len = n
+            .checked_mul(2)
+            .ok_or_else(|| parse_error(&format!("'{name}' has too many points")))?;
+        let flat = self.take_slice(len, name)?;
+        Ok(flat.chunks_exact(2).map(|p| [p[0], p[1]]).collect())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_numbers() -> Result<()> {
+        let values = numbers("  1.5E+00-2.5e-01 3\n 0.1D+01 -4")?;
+        assert_eq!(values, vec![1.5, -0.25, 3.0, 1.0, -4.0]);
+        assert!(matches!(numbers("1.0 abc"), Err(NcError::EqdskParse(..))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_truncated() {
+        let text = "  EFIT    0 3 3\n 1.0 2.0 1.0 0.5 0.0\n";
+        assert!(matches!(Eqdsk::parse(text), Err(NcError::EqdskParse(..))));
+        assert!(matches!(
+            Eqdsk::parse("no dimensions\n"),
+            Err(NcError::EqdskParse(..))
+        ));
+    }
+
+    #[test]
+    fn test_oversized_counts() {
+        let huge = "  EFIT    0 4294967296 4294967296\n";
+        assert!(matches!(Eqdsk::parse(huge), Err(NcError::EqdskParse(..))));
+
+        // A valid 2x2 grid, whose boundary count saturates the conversion to usize.
+        let body = vec!["1.0"; 34].join(" ");
+        let text = format!("  EFIT    0 2 2\n{body} 1e300 0\n");
+        assert!(matches!(Eqdsk::parse(&text), Err(NcError::EqdskParse(..))));
+        assert!(Eqdsk::parse(&format!("  EFIT    0 2 2\n{body} 0 0\n")).is_ok());
+    }
+}
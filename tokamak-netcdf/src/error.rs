@@ -5,6 +5,7 @@ use std::path::PathBuf;
 pub enum NcError {
     /// Catches errors from the wrapped netcdf library.
     /// Lower level error: netcdf::Error::Netcdf.
+    #[cfg(feature = "netcdf")]
     #[error("Wrapped library Error: {reason} ({source}).")]
     LibraryError {
         #[source]
@@ -20,6 +21,10 @@ pub enum NcError {
     #[error("'{0}' variable not found.")]
     VariableNotFound(Box<str>),
 
+    /// Group does not exist.
+    #[error("'{0}' group not found.")]
+    GroupNotFound(Box<str>),
+
     /// Variable exists, but has no data.
     #[error("'{0}' variable is empty.")]
     EmptyVariable(Box<str>),
@@ -36,8 +41,101 @@ pub enum NcError {
     #[error("'{0}' variable is not 2-dimensional.")]
     Not2D(Box<str>),
 
+    /// Variable's shape does not match the (ψ, θ) grid of the coordinates.
+    #[error("'{name}' variable has shape {found:?}, expected {expected:?}.")]
+    ShapeMismatch {
+        name: Box<str>,
+        expected: Box<[usize]>,
+        found: Box<[usize]>,
+    },
+
+    /// Variable has fewer values than the axis extrapolation needs.
+    #[error("'{name}' variable has {found} values, axis extrapolation needs {needed}.")]
+    NotEnoughPoints {
+        name: Box<str>,
+        needed: usize,
+        found: usize,
+    },
+
+    /// Coordinate is not strictly increasing.
+    #[error("'{0}' coordinate is not strictly increasing.")]
+    NonMonotonic(Box<str>),
+
+    /// θ coordinate does not cover a full period.
+    #[error("'{name}' coordinate spans {span}, which does not cover a full period.")]
+    IncompletePeriod { name: Box<str>, span: f64 },
+
+    /// 2D variable's dimensions are not those of its coordinate variables, in either order.
+    #[error("'{name}' variable has dimensions ({found}), expected ({expected}) or the reverse.")]
+    DimensionMismatch {
+        name: Box<str>,
+        expected: Box<str>,
+        found: Box<str>,
+    },
+
+    /// Variable contains NaN or infinite values.
+    #[error("'{name}' variable contains {count} NaN or infinite value(s).")]
+    NonFinite { name: Box<str>, count: usize },
+
+    /// Variable contains cells equal to its `_FillValue` or `missing_value` attribute.
+    #[error("'{name}' variable contains {count} fill or missing value(s).")]
+    MaskedValues { name: Box<str>, count: usize },
+
+    /// Variable contains zero or negative values, where only positive ones are allowed.
+    #[error("'{name}' variable contains {count} non-positive value(s).")]
+    NonPositive { name: Box<str>, count: usize },
+
+    /// Requested time slice does not exist.
+    #[error("Time index {index} is out of range for {len} time slice(s).")]
+    TimeIndexOutOfRange { index: usize, len: usize },
+
+    /// Error reading a non-NetCDF equilibrium file.
+    #[error("Error reading '{path}': {source}.")]
+    ReadError {
+        #[source]
+        source: std::io::Error,
+        path: PathBuf,
+    },
+
+    /// EQDSK file is truncated or contains invalid values.
+    #[error("Invalid EQDSK file: {0}.")]
+    EqdskParse(Box<str>),
+
+    /// File is not a valid NetCDF classic or 64-bit offset file.
+    #[error("Invalid NetCDF classic file: {0}.")]
+    ClassicFormat(Box<str>),
+
+    /// IMAS equilibrium IDS lacks data the importer needs.
+    #[error("Unsupported IMAS equilibrium: {0}.")]
+    ImasFormat(Box<str>),
+
+    /// COCOS index is not one of 1-8 or 11-18.
+    #[error("COCOS {0} does not exist.")]
+    InvalidCocos(u8),
+
+    /// Flux surface could not be traced within the (R, Z) grid.
+    #[error("Flux surface ψ_N = {0} could not be traced within the (R, Z) grid.")]
+    SurfaceNotFound(f64),
+
+    /// Equilibrium has a toroidal dependence, which `NcData` cannot represent.
+    #[error("Equilibrium is not axisymmetric.")]
+    NonAxisymmetric,
+
+    /// Requested (ψ, θ) grid is invalid.
+    #[error("Invalid grid: {0}.")]
+    InvalidGrid(Box<str>),
+
+    /// Evaluation point is outside the equilibrium's domain, with `DomainPolicy::Error`.
+    #[error("'{name}' = {value} is outside the domain [{}, {}].", span.0, span.1)]
+    OutOfDomain {
+        name: Box<str>,
+        value: f64,
+        span: (f64, f64),
+    },
+
     /// Errors from netcdf's `get_values()` functions. Those are hard to track but should be
     /// basically unreachable.
+    #[cfg(feature = "netcdf")]
     #[error("Error extracting values from '{name}' variable: {source}.")]
     GetValuesError {
         #[source]
@@ -47,6 +145,7 @@ pub enum NcError {
 
     /// Supplied wrong `Extents` dimensionality to `get_values()`.
     /// Lower level error: netcdf::Error::DimensionalityMismatch.
+    #[cfg(feature = "netcdf")]
     #[error("Error extracting '{name}': {source}.")]
     DimensionError {
         #[source]
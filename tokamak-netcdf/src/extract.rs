@@ -1,143 +1,361 @@
 //! Functions for extracting and checking data from the NetCDF file.
 
-use crate::{NcError, Result};
-use ndarray::{Array1, Array2, ArrayView, Axis, array};
+use std::collections::BTreeMap;
+
+use crate::axis::{AxisExtrapolation, prepend_axis_row, prepend_axis_value};
+use crate::backend::{NcFileRef, Variable};
+use crate::{AttributeValue, NcError, Result};
+use ndarray::{Array1, Array2, ArrayD, ArrayView1, IxDyn};
+
+/// A NetCDF file to extract variables from, optionally restricted to a single time slice.
+pub(crate) struct NcSource<'f> {
+    file: NcFileRef<'f>,
+    /// Path of the group that variable names are relative to, empty for the root group.
+    group: &'f str,
+    /// Name of the time dimension, and index of the slice.
+    time: Option<(&'f str, usize)>,
+}
+
+impl<'f> NcSource<'f> {
+    /// Extracts whole variables from `file`.
+    pub(crate) fn new(file: impl Into<NcFileRef<'f>>) -> Self {
+        Self {
+            file: file.into(),
+            group: "",
+            time: None,
+        }
+    }
+
+    /// Extracts the `index`-th slice of variables whose first dimension is `time_dim`. Other
+    /// variables are extracted whole.
+    pub(crate) fn at_time(self, time_dim: &'f str, index: usize) -> Self {
+        Self {
+            time: Some((time_dim, index)),
+            ..self
+        }
+    }
+
+    /// Resolves variable names relative to the `group` path, unless they start with '/'.
+    pub(crate) fn in_group(self, group: &'f str) -> Result<Self> {
+        match self.file.has_group(&group_path("", group)) {
+            true => Ok(Self { group, ..self }),
+            false => Err(NcError::GroupNotFound(group.into())),
+        }
+    }
+}
+
+/// Joins a variable `name` to the path of the `group` it is relative to. Names starting with '/'
+/// are absolute. Empty components are dropped, so that the root group's path is empty.
+pub(crate) fn group_path(group: &str, name: &str) -> String {
+    let group = match name.starts_with('/') {
+        true => "",
+        false => group,
+    };
+    group
+        .split('/')
+        .chain(name.split('/'))
+        .filter(|component| !component.is_empty())
+        .collect::<Vec<_>>()
+        .join("/")
+}
 
 /// Extracts a `Variable` fron a NetCDF file.
-fn extract_variable<'a>(f: &'a netcdf::File, name: &'a str) -> Result<netcdf::Variable<'a>> {
-    f.variable(name)
-        .ok_or(NcError::VariableNotFound(name.into()))
+fn extract_variable<'a>(f: &NcSource<'a>, name: &str) -> Result<Variable<'a>> {
+    let path = group_path(f.group, name);
+    f.file
+        .variable(&path)
+        .ok_or_else(|| NcError::VariableNotFound(path.as_str().into()))
 }
 
 /// Checks if a `Variable` is empty.
-fn check_if_empty(var: &netcdf::Variable) -> Result<()> {
-    match var.len() {
+fn check_if_empty(var: &Variable) -> Result<()> {
+    match var.dimensions().iter().map(|dim| dim.1).product::<usize>() {
         1.. => Ok(()),
         0 => Err(NcError::EmptyVariable(var.name().into())),
     }
 }
 
+/// Returns the index selecting the source's time slice of a `Variable`, and the shape of the
+/// selected values. Variables without a leading time dimension are selected whole.
+fn selection(f: &NcSource, var: &Variable) -> Result<(Option<usize>, Vec<usize>)> {
+    let dims = var.dimensions();
+    let mut shape: Vec<usize> = dims.iter().map(|dim| dim.1).collect();
+
+    if let Some((time_dim, index)) = f.time
+        && dims.first().is_some_and(|dim| dim.0 == time_dim)
+    {
+        if index >= shape[0] {
+            return Err(NcError::TimeIndexOutOfRange {
+                index,
+                len: shape[0],
+            });
+        }
+        shape.remove(0);
+        return Ok((Some(index), shape));
+    }
+    Ok((None, shape))
+}
+
+/// Numeric types that variables can be extracted as. Values are read as `f64` and decoded
+/// before conversion, so that packed variables can be extracted as any type.
+pub(crate) trait NcValue: Copy {
+    fn from_f64(x: f64) -> Self;
+}
+
+impl NcValue for f64 {
+    fn from_f64(x: f64) -> Self {
+        x
+    }
+}
+
+impl NcValue for i32 {
+    fn from_f64(x: f64) -> Self {
+        x as i32
+    }
+}
+
+/// Decodes a variable's raw values following the CF conventions: cells equal to `_FillValue` or
+/// `missing_value` are rejected, and the rest are unpacked as `raw * scale_factor + add_offset`.
+fn decode<'a>(var: &Variable, values: impl IntoIterator<Item = &'a mut f64>) -> Result<()> {
+    let numbers = |attribute: &str| {
+        var.attribute(attribute)
+            .and_then(|value| value.to_numbers())
+            .unwrap_or_default()
+    };
+    let masks: Vec<f64> = [numbers("_FillValue"), numbers("missing_value")].concat();
+    let scale = numbers("scale_factor").first().copied().unwrap_or(1.0);
+    let offset = numbers("add_offset").first().copied().unwrap_or(0.0);
+
+    let is_masked = |x: f64| {
+        masks
+            .iter()
+            .any(|&mask| x == mask || (x.is_nan() && mask.is_nan()))
+    };
+    let mut count = 0;
+    for x in values {
+        match is_masked(*x) {
+            true => count += 1,
+            false => *x = *x * scale + offset,
+        }
+    }
+    match count {
+        0 => Ok(()),
+        _ => Err(NcError::MaskedValues {
+            name: var.name().into(),
+            count,
+        }),
+    }
+}
+
+/// Reads the values of a `Variable` at the selected time `index`, and decodes them.
+fn read(var: &Variable, index: Option<usize>) -> Result<Vec<f64>> {
+    let mut values = var.values(index)?;
+    decode(var, values.iter_mut())?;
+    Ok(values)
+}
+
 /// Extracts a scalar (0D) `Variable`'s value.
-pub(crate) fn extract_scalar<T>(f: &netcdf::File, name: &str) -> Result<T>
-where
-    T: netcdf::NcTypeDescriptor + std::marker::Copy,
-{
+pub(crate) fn extract_scalar<T: NcValue>(f: &NcSource, name: &str) -> Result<T> {
     use crate::NcError::*;
 
     let var = extract_variable(f, name)?;
     check_if_empty(&var)?;
 
-    // `var.dimensions()` is () for netcdf's scalar `Variables`. This is probably equivalent to
-    // `var.len() == 0`
-    if !var.dimensions().is_empty() {
+    // The selected shape is () for netcdf's scalar `Variables` and for a time slice of a
+    // variable with only a time dimension.
+    let (index, shape) = selection(f, &var)?;
+    if !shape.is_empty() {
         return Err(NotScalar(name.into()));
     }
 
-    match var.get_value::<T, _>(..) {
-        Ok(value) => Ok(value),
-        Err(err) => Err(NcError::GetValuesError {
-            name: var.name().into(),
-            source: err,
-        }),
+    match read(&var, index)?.first() {
+        Some(&value) => Ok(T::from_f64(value)),
+        None => Err(EmptyVariable(var.name().into())),
     }
 }
 
-/// Extracts a 1D `Variable` and returns its values.
-pub(crate) fn extract_1d_var<T>(f: &netcdf::File, name: &str) -> Result<Array1<T>>
-where
-    T: netcdf::NcTypeDescriptor + std::marker::Copy + std::default::Default,
-{
+/// Extracts a 1D `Variable` and returns its decoded values.
+pub(crate) fn extract_1d_var<T: NcValue>(f: &NcSource, name: &str) -> Result<Array1<T>> {
     let var = extract_variable(f, name)?;
     check_if_empty(&var)?;
 
-    if var.dimensions().len() != 1 {
+    let (index, shape) = selection(f, &var)?;
+    if shape.len() != 1 {
         return Err(NcError::Not1D(var.name().into()));
     }
 
-    let mut data = Array1::<T>::default(var.len());
+    let values = read(&var, index)?;
+    Ok(Array1::from_iter(values.into_iter().map(T::from_f64)))
+}
 
-    match var.get_into(data.view_mut(), ..) {
-        Ok(()) => Ok(data),
-        Err(err) => Err(NcError::GetValuesError {
-            name: var.name().into(),
-            source: err,
-        }),
+/// Extracts a 2D `Variable` and returns its decoded values as an `ndarray`.
+pub(crate) fn extract_2d_var<T: NcValue>(f: &NcSource, name: &str) -> Result<Array2<T>> {
+    let var = extract_variable(f, name)?;
+    check_if_empty(&var)?;
+
+    let (index, shape) = selection(f, &var)?;
+    if shape.len() != 2 {
+        return Err(NcError::Not2D(var.name().into()));
     }
+
+    // Dimension order is the file's; see `extract_2d_var_on_coords` for (ψ, θ) variables.
+    let values = read(&var, index)?.into_iter().map(T::from_f64).collect();
+    // Safe unwrap(); the backends return as many values as the selected shape holds.
+    Ok(Array2::from_shape_vec((shape[0], shape[1]), values).unwrap())
 }
 
-/// Extracts a 2D `Variable` and returns its values as an `ndarray`.
-pub(crate) fn extract_2d_var<T>(f: &netcdf::File, name: &str) -> Result<Array2<T>>
-where
-    T: netcdf::NcTypeDescriptor + std::marker::Copy + std::default::Default,
-{
+/// Extracts a `Variable` of any dimensionality and returns its decoded values.
+pub(crate) fn extract_nd_var(f: &NcSource, name: &str) -> Result<ArrayD<f64>> {
     let var = extract_variable(f, name)?;
     check_if_empty(&var)?;
 
-    if var.dimensions().len() != 2 {
-        return Err(NcError::Not2D(var.name().into()));
+    let (index, shape) = selection(f, &var)?;
+    // Safe unwrap(); the backends return as many values as the selected shape holds.
+    Ok(ArrayD::from_shape_vec(IxDyn(&shape), read(&var, index)?).unwrap())
+}
+
+/// Returns the names and lengths of a `Variable`'s dimensions, including the time dimension.
+pub(crate) fn extract_dimensions(f: &NcSource, name: &str) -> Result<Vec<(String, usize)>> {
+    Ok(extract_variable(f, name)?.dimensions())
+}
+
+/// Returns the dimension names of the values the source selects from a `Variable`.
+fn selected_dimensions(f: &NcSource, var: &Variable) -> Vec<String> {
+    let mut names: Vec<String> = var.dimensions().into_iter().map(|dim| dim.0).collect();
+    if let Some((time_dim, _)) = f.time
+        && names.first().is_some_and(|name| name == time_dim)
+    {
+        names.remove(0);
     }
+    names
+}
 
-    // Dimension order is (ψ,θ).
-    let dims = var.dimensions().to_vec();
-    let shape = (dims[0].len(), dims[1].len());
+/// Returns the dimension of a 1D coordinate variable.
+fn coordinate_dimension(f: &NcSource, name: &str) -> Result<String> {
+    let var = extract_variable(f, name)?;
+    match selected_dimensions(f, &var).as_slice() {
+        [dim] => Ok(dim.clone()),
+        _ => Err(NcError::Not1D(name.into())),
+    }
+}
 
-    let mut data = Array2::<T>::default(shape);
+/// Extracts a 2D `Variable` defined over the dimensions of the `coords` coordinate variables,
+/// and returns its values in the order of `coords`. Variables stored in the reverse order, as
+/// Fortran-ordered tools write them, are transposed.
+pub(crate) fn extract_2d_var_on_coords<T: NcValue>(
+    f: &NcSource,
+    name: &str,
+    coords: (&str, &str),
+) -> Result<Array2<T>> {
+    let data = extract_2d_var(f, name)?;
+    let found = selected_dimensions(f, &extract_variable(f, name)?);
+    let expected = [
+        coordinate_dimension(f, coords.0)?,
+        coordinate_dimension(f, coords.1)?,
+    ];
+
+    if found == expected {
+        Ok(data)
+    } else if found == [expected[1].clone(), expected[0].clone()] {
+        Ok(data.reversed_axes().as_standard_layout().into_owned())
+    } else {
+        Err(NcError::DimensionMismatch {
+            name: name.into(),
+            expected: expected.join(", ").into(),
+            found: found.join(", ").into(),
+        })
+    }
+}
 
-    match var.get_into(data.view_mut(), (.., ..)) {
-        Ok(()) => Ok(data),
-        Err(err) => Err(NcError::GetValuesError {
-            name: var.name().into(),
-            source: err,
-        }),
+/// Extracts the file's global attributes. The attributes of the groups along the source's group
+/// path are included, overriding those of their parents.
+pub(crate) fn extract_global_attributes(f: &NcSource) -> BTreeMap<Box<str>, AttributeValue> {
+    global_attributes(f)
+        .into_iter()
+        .map(|(name, value)| (name.into(), value))
+        .collect()
+}
+
+/// Returns the attributes of the root group and of the groups along the source's group path, in
+/// that order.
+fn global_attributes(f: &NcSource) -> Vec<(String, AttributeValue)> {
+    let mut attributes = Vec::new();
+    let mut path = String::new();
+    let components = f.group.split('/').filter(|c| !c.is_empty());
+    for component in std::iter::once("").chain(components) {
+        path = group_path(&path, component);
+        attributes.extend(f.file.group_attributes(&path));
     }
+    attributes
+}
+
+/// Extracts a variable's attribute, formatted as a string. Returns `None` if either the variable
+/// or the attribute does not exist.
+pub(crate) fn extract_attribute(f: &NcSource, name: &str, attribute: &str) -> Option<Box<str>> {
+    let var = extract_variable(f, name).ok()?;
+    Some(var.attribute(attribute)?.to_text().into())
+}
+
+/// Extracts a string variable's value. Returns `None` if the variable does not exist or is not
+/// a string.
+pub(crate) fn extract_text(f: &NcSource, name: &str) -> Option<Box<str>> {
+    extract_variable(f, name).ok()?.text()
 }
 
-/// Extracts a variable from the NetCDF file and prepends the first value (value closest to the
-/// magnetic axis) at index 0.
-pub(crate) fn extract_var_with_first_axis_value<T>(
-    f: &netcdf::File,
+/// Turns a missing variable into `None`, propagating any other error. Used for the optional
+/// quantities of the equilibrium.
+pub(crate) fn optional<T>(extracted: Result<T>) -> Result<Option<T>> {
+    match extracted {
+        Ok(value) => Ok(Some(value)),
+        Err(NcError::VariableNotFound(_)) => Ok(None),
+        Err(err) => Err(err),
+    }
+}
+
+/// Extracts a 1D variable from the NetCDF file and prepends its value on the axis, extrapolated
+/// from the file's `psi` grid with `method`.
+pub(crate) fn extract_1d_var_with_axis(
+    f: &NcSource,
     name: &str,
-) -> Result<Array1<T>>
-where
-    T: netcdf::NcTypeDescriptor + std::marker::Copy + std::default::Default,
-{
-    let arr: Array1<T> = extract_1d_var(f, name)?;
-    extract_var_with_axis_value(f, name, arr[0])
+    psi: ArrayView1<f64>,
+    method: AxisExtrapolation,
+) -> Result<Array1<f64>> {
+    prepend_axis_value(name, psi, extract_1d_var(f, name)?, method)
 }
 
-/// Extracts a variable from the NetCDF file and prepends `element` at index 0.
-pub(crate) fn extract_var_with_axis_value<T>(
-    f: &netcdf::File,
+/// Extracts a 2D variable over the `coords` (ψ, θ) coordinates from the NetCDF file and prepends
+/// its row on the axis, extrapolated from the file's `psi` grid with `method`.
+pub(crate) fn extract_2d_var_with_axis(
+    f: &NcSource,
     name: &str,
-    element: T,
-) -> Result<Array1<T>>
-where
-    T: netcdf::NcTypeDescriptor + std::marker::Copy + std::default::Default,
-{
-    let arr: Array1<T> = extract_1d_var(f, name)?;
-    let view = ArrayView::from(&arr);
-    let mut prepend: Array1<T> = array![element];
-    // This is not expected to fail since both arrays are guranteed to be of the same shape (1,).
-    match prepend.append(Axis(0), view) {
-        Ok(()) => Ok(prepend),
-        Err(_) => unreachable!("Shape mismatch in prepending axis value."),
-    }
-}
-
-#[cfg(test)]
+    coords: (&str, &str),
+    psi: ArrayView1<f64>,
+    method: AxisExtrapolation,
+) -> Result<Array2<f64>> {
+    let values = extract_2d_var_on_coords(f, name, coords)?;
+    prepend_axis_row(name, psi, values, method)
+}
+
+#[cfg(all(test, feature = "netcdf"))]
 mod test {
     use super::*;
     use NcError::*;
+    use netcdf::Options;
+    use std::sync::atomic::{AtomicUsize, Ordering};
 
     static VAR_LENGTH: usize = 5;
+    /// Coordinate variables of the (dim1, dim2) variables.
+    const COORDS: (&str, &str) = ("coord", "var");
 
-    /// Creates a phony NetCDF file for use across the tests.
+    /// Creates a phony, in-memory NetCDF file for use across the tests. Each file gets a unique
+    /// name, and nothing is written to disk, so that tests can run in parallel.
     fn phony_netcdf() -> std::result::Result<netcdf::FileMut, netcdf::Error> {
-        let path = std::env::temp_dir().join("phony.nc");
-        let path_str = path.to_str().unwrap();
+        static COUNTER: AtomicUsize = AtomicUsize::new(0);
+        let name = format!("phony_{}.nc", COUNTER.fetch_add(1, Ordering::Relaxed));
+        let path = std::env::temp_dir().join(name);
 
-        let mut f = netcdf::create(path_str)?;
-        std::fs::remove_file(path).unwrap();
+        let mut f = netcdf::create_with(&path, Options::NETCDF4 | Options::DISKLESS)?;
+        assert!(!path.exists());
 
         f.add_dimension("dim1", VAR_LENGTH)?;
         f.add_dimension("dim2", VAR_LENGTH)?;
@@ -146,7 +364,9 @@ mod test {
         f.add_dimension("empty_dim", 0)?;
         f.add_variable::<f64>("empty_var", &["empty_dim"])?;
 
+        f.add_variable::<f64>("coord", &["dim1"])?;
         f.add_variable::<f64>("2dvar", &["dim1", "dim2"])?;
+        f.add_variable::<f64>("2dvar_t", &["dim2", "dim1"])?;
         f.add_variable::<i32>("int_var", &["dim1"])?;
 
         f.add_variable::<i32>("number", &[])?
@@ -156,22 +376,24 @@ mod test {
 
     #[test]
     fn test_extract_variable() {
-        let f = phony_netcdf().unwrap();
+        let file = phony_netcdf().unwrap();
+        let f = NcSource::new(&file);
         extract_variable(&f, "var").unwrap();
         assert!(matches!(
-            extract_variable(&f, "not_a_var").unwrap_err(),
-            VariableNotFound(_)
+            extract_variable(&f, "not_a_var"),
+            Err(VariableNotFound(_))
         ));
     }
 
     #[test]
     fn test_check_if_empty() -> Result<()> {
-        let f = phony_netcdf().unwrap();
+        let file = phony_netcdf().unwrap();
+        let f = NcSource::new(&file);
         let var = extract_variable(&f, "var")?;
         let empty_var = extract_variable(&f, "empty_var")?;
 
-        assert_eq!(var.len(), VAR_LENGTH);
-        assert_eq!(empty_var.len(), 0);
+        assert_eq!(var.dimensions(), [("dim2".into(), VAR_LENGTH)]);
+        assert_eq!(empty_var.dimensions(), [("empty_dim".into(), 0)]);
         assert!(matches!(
             check_if_empty(&empty_var).unwrap_err(),
             EmptyVariable(_)
@@ -181,7 +403,8 @@ mod test {
 
     #[test]
     fn test_extract_scalar() -> Result<()> {
-        let f = phony_netcdf().unwrap();
+        let file = phony_netcdf().unwrap();
+        let f = NcSource::new(&file);
         let scalar: i32 = extract_scalar(&f, "number")?;
         let not_a_scalar = extract_scalar::<f64>(&f, "var");
 
@@ -192,7 +415,8 @@ mod test {
 
     #[test]
     fn test_extract_1d_var() {
-        let f = phony_netcdf().unwrap();
+        let file = phony_netcdf().unwrap();
+        let f = NcSource::new(&file);
         let values1d = extract_1d_var::<f64>(&f, "var");
         let values2d = extract_1d_var::<f64>(&f, "2dvar");
         let empty_values = extract_1d_var::<f64>(&f, "empty_var");
@@ -206,7 +430,8 @@ mod test {
 
     #[test]
     fn test_ectract_2d_var() {
-        let f = phony_netcdf().unwrap();
+        let file = phony_netcdf().unwrap();
+        let f = NcSource::new(&file);
         let values2d = extract_2d_var::<f64>(&f, "2dvar");
         let values1d = extract_2d_var::<f64>(&f, "var");
         let empty_values = extract_2d_var::<f64>(&f, "empty_var");
@@ -218,24 +443,180 @@ mod test {
         assert!(matches!(err_values.unwrap_err(), VariableNotFound(_)));
     }
 
+    #[test]
+    fn test_optional() {
+        let file = phony_netcdf().unwrap();
+        let f = NcSource::new(&file);
+
+        assert!(
+            optional(extract_1d_var::<f64>(&f, "var"))
+                .unwrap()
+                .is_some()
+        );
+        assert!(
+            optional(extract_1d_var::<f64>(&f, "not_a_var"))
+                .unwrap()
+                .is_none()
+        );
+        assert!(matches!(
+            optional(extract_1d_var::<f64>(&f, "2dvar")).unwrap_err(),
+            Not1D(_)
+        ));
+    }
+
     #[test]
     fn test_axis_value() -> Result<()> {
-        let mut f = phony_netcdf().unwrap();
-        let data: [i32; VAR_LENGTH] = [2, 3, 4, 5, 6];
+        let mut file = phony_netcdf().unwrap();
+        let data: [f64; VAR_LENGTH] = [2.0, 3.0, 4.0, 5.0, 6.0];
+        let psi = Array1::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5]);
 
-        f.variable_mut("int_var")
+        file.variable_mut("var")
             .expect("Error extracting mutable variable.")
             .put_values(&data, ..)
             .expect("Error putting values to variable");
+        let f = NcSource::new(&file);
+
+        assert_eq!(
+            Array1::from_vec(vec![1.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+            extract_1d_var_with_axis(&f, "var", psi.view(), AxisExtrapolation::Constant(1.0))?
+        );
+        assert_eq!(
+            Array1::from_vec(vec![2.0, 2.0, 3.0, 4.0, 5.0, 6.0]),
+            extract_1d_var_with_axis(&f, "var", psi.view(), AxisExtrapolation::Nearest)?
+        );
+        assert_eq!(
+            Array1::from_vec(data.to_vec()),
+            extract_1d_var_with_axis(&f, "var", psi.view(), AxisExtrapolation::None)?
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_axis_row() -> Result<()> {
+        let mut file = phony_netcdf().unwrap();
+        let data = Array2::from_shape_fn((VAR_LENGTH, VAR_LENGTH), |(i, j)| (i + j) as f64);
+        let psi = Array1::from_vec(vec![0.1, 0.2, 0.3, 0.4, 0.5]);
+
+        file.variable_mut("2dvar")
+            .expect("Error extracting mutable variable.")
+            .put(data.view(), (.., ..))
+            .expect("Error putting values to variable");
+        let f = NcSource::new(&file);
+
+        let nearest = AxisExtrapolation::Nearest;
+        let with_axis = extract_2d_var_with_axis(&f, "2dvar", COORDS, psi.view(), nearest)?;
+        assert_eq!(with_axis.dim(), (VAR_LENGTH + 1, VAR_LENGTH));
+        assert!(with_axis.row(0).iter().all(|&x| x == 2.0));
+        assert_eq!(with_axis.row(1), data.row(0));
+        assert!(matches!(
+            extract_2d_var_with_axis(&f, "var", COORDS, psi.view(), nearest).unwrap_err(),
+            Not2D(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_decode() -> Result<()> {
+        let mut file = phony_netcdf().unwrap();
+        let mut packed = file.add_variable::<i16>("packed", &["dim1"]).unwrap();
+        packed.put_attribute("scale_factor", 0.5).unwrap();
+        packed.put_attribute("add_offset", 1.0f32).unwrap();
+        packed.put_values(&[0i16, 1, 2, 3, 4], ..).unwrap();
+
+        let mut filled = file
+            .add_variable::<f64>("filled", &["dim1", "dim2"])
+            .unwrap();
+        filled.put_attribute("_FillValue", -1.0).unwrap();
+        filled
+            .put_attribute("missing_value", vec![-2.0, -3.0])
+            .unwrap();
+        let data = Array2::from_shape_fn((VAR_LENGTH, VAR_LENGTH), |(i, j)| match (i, j) {
+            (0, 0) => -1.0,
+            (4, _) => -3.0,
+            _ => 1.0,
+        });
+        filled.put(data.view(), (.., ..)).unwrap();
+        let f = NcSource::new(&file);
 
         assert_eq!(
-            Array1::<i32>::from_vec(vec![1, 2, 3, 4, 5, 6]),
-            extract_var_with_axis_value(&f, "int_var", 1)?
+            extract_1d_var::<f64>(&f, "packed")?,
+            Array1::from_vec(vec![1.0, 1.5, 2.0, 2.5, 3.0])
         );
+        assert!(matches!(
+            extract_2d_var::<f64>(&f, "filled").unwrap_err(),
+            MaskedValues { count: 6, .. }
+        ));
+        // Variables without packing attributes are read as they are.
+        assert_eq!(extract_scalar::<f64>(&f, "number")?, 18.0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_extract_2d_var_on_coords() -> Result<()> {
+        let mut file = phony_netcdf().unwrap();
+        let data = Array2::from_shape_fn((VAR_LENGTH, VAR_LENGTH), |(i, j)| (10 * i + j) as f64);
+        file.variable_mut("2dvar")
+            .unwrap()
+            .put(data.view(), (.., ..))
+            .unwrap();
+        file.variable_mut("2dvar_t")
+            .unwrap()
+            .put(data.t().as_standard_layout().view(), (.., ..))
+            .unwrap();
+        let f = NcSource::new(&file);
+
+        assert_eq!(extract_2d_var_on_coords::<f64>(&f, "2dvar", COORDS)?, data);
         assert_eq!(
-            Array1::<i32>::from_vec(vec![2, 2, 3, 4, 5, 6]),
-            extract_var_with_first_axis_value(&f, "int_var")?
+            extract_2d_var_on_coords::<f64>(&f, "2dvar_t", COORDS)?,
+            data
         );
+        assert!(matches!(
+            extract_2d_var_on_coords::<f64>(&f, "2dvar", ("coord", "coord")).unwrap_err(),
+            DimensionMismatch { .. }
+        ));
+        assert!(matches!(
+            extract_2d_var_on_coords::<f64>(&f, "2dvar", ("coord", "2dvar")).unwrap_err(),
+            Not1D(_)
+        ));
+        Ok(())
+    }
+
+    #[test]
+    fn test_group_path() {
+        assert_eq!(group_path("", "var"), "var");
+        assert_eq!(group_path("eq/boozer", "var"), "eq/boozer/var");
+        assert_eq!(group_path("/eq/", "sub/var"), "eq/sub/var");
+        assert_eq!(group_path("eq", "/profiles/q"), "profiles/q");
+    }
+
+    #[test]
+    fn test_extract_from_group() -> Result<()> {
+        let mut file = phony_netcdf().unwrap();
+        file.variable_mut("var")
+            .unwrap()
+            .put_values(&[0.0; VAR_LENGTH], ..)
+            .unwrap();
+        file.add_group("eq/boozer")
+            .unwrap()
+            .add_variable::<f64>("var", &["dim1"])
+            .unwrap()
+            .put_values(&[1.0; VAR_LENGTH], ..)
+            .unwrap();
+
+        let f = NcSource::new(&file).in_group("eq/boozer")?;
+        assert_eq!(extract_1d_var::<f64>(&f, "var")?, Array1::ones(VAR_LENGTH));
+        assert_eq!(
+            extract_1d_var::<f64>(&f, "/var")?,
+            Array1::zeros(VAR_LENGTH)
+        );
+        assert!(matches!(
+            extract_1d_var::<f64>(&f, "missing/var").unwrap_err(),
+            VariableNotFound(_)
+        ));
+        assert!(matches!(
+            NcSource::new(&file).in_group("eq/vmec"),
+            Err(GroupNotFound(_))
+        ));
         Ok(())
     }
 }
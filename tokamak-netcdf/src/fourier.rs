@@ -0,0 +1,177 @@
+//! Poloidal Fourier decomposition of the magnetic field strength.
+
+use std::f64::consts::{PI, TAU};
+
+use ndarray::{Array1, Array2, ArrayView1};
+
+use crate::validate::{incomplete_period, period_len};
+use crate::{Bfield, Coords, NcData, NcError, NcSchema, Result};
+
+/// Poloidal Fourier coefficients of the magnetic field strength on each flux surface, so that
+///
+/// B(ψ, θ) = Σ_m \[cos\_m(ψ) cos(mθ) + sin\_m(ψ) sin(mθ)\], for m = 0, ..., m_max.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BfieldFourier {
+    /// Cosine coefficients, with shape (ψ, m_max + 1).
+    pub cos: Array2<f64>,
+    /// Sine coefficients, with shape (ψ, m_max + 1). The m = 0 column is always zero.
+    pub sin: Array2<f64>,
+}
+
+impl NcData {
+    /// Calculates the poloidal Fourier coefficients of B on each surface, up to mode `m_max`,
+    /// using the equilibrium's θ grid.
+    ///
+    /// The θ grid must cover a full period, with or without the 2π endpoint, and have at least
+    /// 2·m_max + 1 distinct points. The coefficients are computed with the trapezoidal rule,
+    /// which on a uniform grid is the discrete Fourier transform.
+    ///
+    /// ```
+    /// # use tokamak_netcdf::{CircularTokamak, NcError};
+    /// # fn main() -> Result<(), NcError> {
+    /// let nc_data = CircularTokamak::default().to_nc_data()?;
+    /// let fourier = nc_data.bfield_fourier(4)?;
+    /// assert!(fourier.is_up_down_symmetric(1e-10));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn bfield_fourier(&self, m_max: usize) -> Result<BfieldFourier> {
+        self.bfield.fourier(&self.coords, &self.schema, m_max)
+    }
+}
+
+impl Bfield {
+    /// Calculates the poloidal Fourier coefficients of B on the grid of `coords`, like
+    /// [`NcData::bfield_fourier`]. Errors name the variables after `schema`.
+    pub(crate) fn fourier(
+        &self,
+        coords: &Coords,
+        schema: &NcSchema,
+        m_max: usize,
+    ) -> Result<BfieldFourier> {
+        let theta = coords.theta.view();
+        if let Some(span) = incomplete_period(theta.as_slice().unwrap_or(&[])) {
+            return Err(NcError::IncompletePeriod {
+                name: schema.theta.clone(),
+                span,
+            });
+        }
+        let n = period_len(theta);
+        if 2 * m_max + 1 > n {
+            return Err(NcError::InvalidGrid(
+                format!("{n} theta points cannot resolve modes up to m = {m_max}").into(),
+            ));
+        }
+
+        // Trapezoidal weights of the closed, periodic grid.
+        let weights = Array1::from_shape_fn(n, |j| {
+            let next = match j + 1 < n {
+                true => theta[j + 1],
+                false => theta[0] + TAU,
+            };
+            let previous = match j > 0 {
+                true => theta[j - 1],
+                false => theta[n - 1] - TAU,
+            };
+            (next - previous) / 2.0
+        });
+
+        let shape = (self.b.nrows(), m_max + 1);
+        let mut cos = Array2::zeros(shape);
+        let mut sin = Array2::zeros(shape);
+        for m in 0..=m_max {
+            let norm = match m {
+                0 => TAU,
+                _ => PI,
+            };
+            for j in 0..n {
+                let angle = m as f64 * theta[j];
+                let (wc, ws) = (weights[j] * angle.cos(), weights[j] * angle.sin());
+                for k in 0..shape.0 {
+                    cos[[k, m]] += wc * self.b[[k, j]] / norm;
+                    if m > 0 {
+                        sin[[k, m]] += ws * self.b[[k, j]] / norm;
+                    }
+                }
+            }
+        }
+
+        Ok(BfieldFourier { cos, sin })
+    }
+}
+
+impl BfieldFourier {
+    /// Returns the highest poloidal mode number.
+    pub fn m_max(&self) -> usize {
+        self.cos.ncols() - 1
+    }
+
+    /// Creates a `Bfield` on the same ψ grid, evaluating the series at `theta`.
+    pub fn to_bfield(&self, theta: ArrayView1<f64>) -> Bfield {
+        Bfield::new(self.series(theta, |_, angle| (angle.cos(), angle.sin())))
+    }
+
+    /// Calculates ∂B/∂θ on the same ψ grid, at `theta`.
+    pub fn dtheta(&self, theta: ArrayView1<f64>) -> Array2<f64> {
+        self.series(theta, |m, angle| (-m * angle.sin(), m * angle.cos()))
+    }
+
+    /// Returns whether B is symmetric about θ = 0, which is the case for an up-down symmetric
+    /// equilibrium whose θ is measured from the outboard midplane. The sine coefficients must
+    /// all be within `tolerance` times the largest cosine coefficient.
+    pub fn is_up_down_symmetric(&self, tolerance: f64) -> bool {
+        let scale = self.cos.iter().fold(0.0, |max: f64, c| max.max(c.abs()));
+        self.sin.iter().all(|s| s.abs() <= tolerance * scale)
+    }
+
+    /// Sums the series at `theta`, with `basis(m, mθ)` returning the factors of the cosine and
+    /// sine coefficients.
+    fn series<F>(&self, theta: ArrayView1<f64>, basis: F) -> Array2<f64>
+    where
+        F: Fn(f64, f64) -> (f64, f64),
+    {
+        Array2::from_shape_fn((self.cos.nrows(), theta.len()), |(k, j)| {
+            (0..=self.m_max())
+                .map(|m| {
+                    let (fc, fs) = basis(m as f64, m as f64 * theta[j]);
+                    fc * self.cos[[k, m]] + fs * self.sin[[k, m]]
+                })
+                .sum()
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ndarray::array;
+
+    #[test]
+    fn test_fourier_nonuniform() -> Result<()> {
+        // Non-uniform grid with the 2π endpoint, and an up-down asymmetric field.
+        let theta = array![0.0, 0.5, 1.0, 2.0, 2.5, 3.0, 4.0, 4.5, 5.5, TAU];
+        let b = |t: f64| 1.0 + 0.1 * t.sin();
+        let bfield = Bfield::new(Array2::from_shape_fn(
+            (2, theta.len()),
+            |(_, j)| b(theta[j]),
+        ));
+        let coords = Coords::new(array![0.0, 1.0], theta.clone(), false);
+        let schema = NcSchema::new().with_theta("theta_b");
+
+        let fourier = bfield.fourier(&coords, &schema, 1)?;
+        assert!((fourier.cos[[0, 0]] - 1.0).abs() < 1e-2);
+        assert!((fourier.sin[[1, 1]] - 0.1).abs() < 1e-2);
+        assert!(!fourier.is_up_down_symmetric(1e-3));
+
+        assert!(matches!(
+            bfield.fourier(&coords, &schema, 5),
+            Err(NcError::InvalidGrid(_))
+        ));
+        let half = Coords::new(array![0.0, 1.0], array![0.0, 1.0, 2.0], false);
+        assert!(matches!(
+            bfield.fourier(&half, &schema, 0),
+            Err(NcError::IncompletePeriod { name, .. }) if &*name == "theta_b"
+        ));
+        Ok(())
+    }
+}
@@ -0,0 +1,71 @@
+//! `Geometry` implementation.
+
+use ndarray::{Array2, ArrayView1};
+
+use crate::{
+    NcSchema, Result,
+    extract::{NcSource, extract_2d_var_with_axis, optional},
+};
+
+/// Representation of the equilibrium's flux surface geometry, as the cylindrical coordinates of
+/// each (ψ, θ) grid point.
+pub struct Geometry {
+    /// Major radius R as a function of ψ and θ.
+    pub r: Array2<f64>,
+    /// Vertical position Z as a function of ψ and θ.
+    pub z: Array2<f64>,
+}
+
+impl Geometry {
+    /// Creates a `Geometry` containing the R(ψ, θ) and Z(ψ, θ) maps from the NetCDF file.
+    /// Returns `None` unless both variables exist.
+    pub(crate) fn build(
+        f: &NcSource,
+        schema: &NcSchema,
+        file_psi: ArrayView1<f64>,
+    ) -> Result<Option<Self>> {
+        // The axis row is an estimate of the axis position.
+        let method = schema.axis.geometry;
+        let coords = (&*schema.psi, &*schema.theta);
+        let r = optional(extract_2d_var_with_axis(
+            f, &schema.r, coords, file_psi, method,
+        ))?;
+        let z = optional(extract_2d_var_with_axis(
+            f, &schema.z, coords, file_psi, method,
+        ))?;
+
+        match (r, z) {
+            (Some(r), Some(z)) => Ok(Some(Geometry { r, z })),
+            _ => Ok(None),
+        }
+    }
+}
+
+impl std::fmt::Debug for Geometry {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let span = |arr: &Array2<f64>| {
+            arr.iter()
+                .fold((f64::INFINITY, f64::NEG_INFINITY), |(min, max), &x| {
+                    (min.min(x), max.max(x))
+                })
+        };
+        let (r_span, z_span) = (span(&self.r), span(&self.z));
+
+        writeln!(f, "Geometry: {{")?;
+        writeln!(
+            f,
+            "        R = [{:.7}, ..., {:.7}], shape = {:?},",
+            r_span.0,
+            r_span.1,
+            self.r.dim(),
+        )?;
+        writeln!(
+            f,
+            "        Z = [{:.7}, ..., {:.7}], shape = {:?},",
+            z_span.0,
+            z_span.1,
+            self.z.dim(),
+        )?;
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,517 @@
+//! IMAS equilibrium IDS reader.
+//!
+//! Two layouts are read, both storing each IDS in a group named after it:
+//!
+//! - IMAS netCDF files, as written by IMAS-Python, name each quantity by its path in the IDS with
+//!   `.` separators, such as `time_slice.profiles_1d.psi`.
+//! - HDF5 files written by the Access Layer's HDF5 backend name it with `&` separators, and `[]`
+//!   after each array of structures, such as `time_slice[]&profiles_1d&psi`. Arrays are padded
+//!   to the largest entry of the arrays of structures above them, and the actual sizes are
+//!   stored in `<name>_SHAPE` datasets.
+//!
+//! In both, every array of structures adds a leading dimension to the quantities below it, so
+//! those of a time slice have a leading time slice dimension, and those of `profiles_2d` a second
+//! one. Data dimensions follow the IDS's order, e.g. (R, Z) for `profiles_2d.psi`. HDF5 files are
+//! read through libnetcdf, which opens netCDF-4 files and most plain HDF5 files alike.
+
+use std::f64::consts::TAU;
+use std::path::{Path, PathBuf};
+
+use ndarray::{Array, Array1, Array2, ArrayD, Axis, Dimension, Slice, s};
+
+use crate::backend::NcFile;
+use crate::boozer::{BoozerGrid, FluxMap};
+use crate::extract::{
+    NcSource, extract_1d_var, extract_dimensions, extract_global_attributes, extract_nd_var,
+    extract_scalar, extract_text, optional,
+};
+use crate::metadata::checksum;
+use crate::open::open_file;
+use crate::resample::linear_weights;
+use crate::validate::check_shape;
+use crate::{AttributeValue, NcData, NcError, NcSchema, Result};
+
+/// Group of the equilibrium IDS.
+const IDS: &str = "equilibrium";
+
+/// `profiles_2d.grid_type.index` of rectangular (R, Z) grids.
+const RECTANGULAR: i32 = 1;
+
+/// A time slice quantity, whose first dimension is the time slice dimension.
+const PSI_AXIS: &str = "time_slice.global_quantities.psi_axis";
+
+/// Arrays of structures along the paths the reader uses.
+const ARRAYS_OF_STRUCTURES: [&str; 2] = ["time_slice", "profiles_2d"];
+
+/// Layout of an IDS's quantities in a file.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Layout {
+    /// IMAS-Python's netCDF layout.
+    Netcdf,
+    /// Layout of the Access Layer's HDF5 backend.
+    AccessLayer,
+}
+
+impl Layout {
+    /// Finds the layout of the IDS, and returns it with the name of the time slice dimension.
+    fn detect(ids: &NcSource) -> Result<(Self, String)> {
+        let (layout, dims) = match extract_dimensions(ids, &Self::Netcdf.name(PSI_AXIS)) {
+            Err(NcError::VariableNotFound(_)) => (
+                Self::AccessLayer,
+                extract_dimensions(ids, &Self::AccessLayer.name(PSI_AXIS))?,
+            ),
+            dims => (Self::Netcdf, dims?),
+        };
+        match dims.into_iter().next() {
+            Some((name, _)) => Ok((layout, name)),
+            None => Err(imas_error(
+                "time slice quantities have no time slice dimension",
+            )),
+        }
+    }
+
+    /// Returns the variable name of the quantity at the IDS `path`, given with `.` separators.
+    fn name(self, path: &str) -> String {
+        match self {
+            Self::Netcdf => path.into(),
+            Self::AccessLayer => {
+                let components: Vec<&str> = path.split('.').collect();
+                let last = components.len() - 1;
+                components
+                    .iter()
+                    .enumerate()
+                    .map(
+                        |(k, &c)| match k < last && ARRAYS_OF_STRUCTURES.contains(&c) {
+                            true => format!("{c}[]"),
+                            false => c.into(),
+                        },
+                    )
+                    .collect::<Vec<_>>()
+                    .join("&")
+            }
+        }
+    }
+
+    /// Extracts the actual sizes of the padded quantity at `path`, with a last axis holding the
+    /// size of each dimension. Returns `None` if the quantity is not padded.
+    fn sizes(self, f: &NcSource, path: &str) -> Result<Option<ArrayD<f64>>> {
+        match self {
+            Self::Netcdf => Ok(None),
+            Self::AccessLayer => optional(extract_nd_var(f, &format!("{}_SHAPE", self.name(path)))),
+        }
+    }
+
+    /// Returns the Data Dictionary version of the IDS, from the group's attribute or from
+    /// `ids_properties.version_put.data_dictionary`.
+    fn dd_version(self, ids: &NcSource) -> Option<Box<str>> {
+        match extract_global_attributes(ids).remove("data_dictionary_version") {
+            Some(version) => Some(version.to_text().into()),
+            None => extract_text(
+                ids,
+                &self.name("ids_properties.version_put.data_dictionary"),
+            ),
+        }
+    }
+}
+
+/// A COCOS convention for the signs and the normalisation of the poloidal flux and of the
+/// coordinates, as defined by O. Sauter and S. Yu. Medvedev, Comput. Phys. Commun. 184 (2013) 293.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Cocos(u8);
+
+impl Cocos {
+    /// Convention of EQDSK G-files, which the Boozer mapping expects.
+    pub const EQDSK: Cocos = Cocos(1);
+    /// Convention of the IMAS Data Dictionary, up to version 3.
+    pub const IMAS_DD3: Cocos = Cocos(11);
+    /// Convention of the IMAS Data Dictionary, from version 4.
+    pub const IMAS_DD4: Cocos = Cocos(17);
+
+    /// Creates a convention from its index, which must be in 1-8 or 11-18.
+    pub fn new(index: u8) -> Result<Self> {
+        match index {
+            1..=8 | 11..=18 => Ok(Self(index)),
+            _ => Err(NcError::InvalidCocos(index)),
+        }
+    }
+
+    /// Returns the convention's index.
+    pub fn index(self) -> u8 {
+        self.0
+    }
+
+    /// Returns the factor converting ψ from this convention to `to`, keeping the directions of
+    /// the plasma current and the toroidal field.
+    pub fn psi_factor(self, to: Cocos) -> f64 {
+        let sign = self.sigma_r_phi_z() * to.sigma_r_phi_z() * self.sigma_bp() * to.sigma_bp();
+        sign * TAU.powi(to.e_bp() - self.e_bp())
+    }
+
+    /// Returns the factor converting the safety factor from this convention to `to`.
+    pub fn q_factor(self, to: Cocos) -> f64 {
+        self.sigma_rho_theta_phi() * to.sigma_rho_theta_phi()
+    }
+
+    /// Returns the factor converting toroidal components, such as F, B0 and Ip, from this
+    /// convention to `to`.
+    pub fn toroidal_factor(self, to: Cocos) -> f64 {
+        self.sigma_r_phi_z() * to.sigma_r_phi_z()
+    }
+
+    /// 1 if ψ is the total poloidal flux, 0 if it is per radian.
+    fn e_bp(self) -> i32 {
+        i32::from(self.0 >= 11)
+    }
+
+    fn sigma_bp(self) -> f64 {
+        match self.0 % 10 {
+            1 | 2 | 5 | 6 => 1.0,
+            _ => -1.0,
+        }
+    }
+
+    /// +1 for right-handed (R, φ, Z), -1 for right-handed (R, Z, φ).
+    fn sigma_r_phi_z(self) -> f64 {
+        match self.0 % 2 {
+            1 => 1.0,
+            _ => -1.0,
+        }
+    }
+
+    /// +1 for right-handed (ρ, θ, φ), -1 for right-handed (ρ, φ, θ).
+    fn sigma_rho_theta_phi(self) -> f64 {
+        match self.0 % 10 {
+            1 | 2 | 7 | 8 => 1.0,
+            _ => -1.0,
+        }
+    }
+}
+
+/// A time slice of an IMAS equilibrium IDS, in SI units and in the convention `cocos`.
+pub struct ImasEquilibrium {
+    /// Convention of the fluxes' and the toroidal components' signs.
+    pub cocos: Cocos,
+    /// Time of the slice in \[*s*\].
+    pub time: f64,
+    /// Major radius where `b0` is given, in \[*m*\].
+    pub r0: f64,
+    /// Vacuum toroidal field at `r0` in \[*T*\].
+    pub b0: f64,
+    /// Magnetic axis' R in \[*m*\].
+    pub rmaxis: f64,
+    /// Magnetic axis' Z in \[*m*\].
+    pub zmaxis: f64,
+    /// Poloidal flux on the axis in \[*Wb*\], or \[*Wb/rad*\] for COCOS 1-8.
+    pub psi_axis: f64,
+    /// Poloidal flux on the boundary, in the units of `psi_axis`.
+    pub psi_boundary: f64,
+    /// Plasma current in \[*A*\].
+    pub ip: f64,
+    /// Poloidal flux grid of the 1D profiles, from the axis to the boundary.
+    pub psi: Array1<f64>,
+    /// Poloidal current function F = R Bφ in \[*T m*\].
+    pub f: Array1<f64>,
+    /// Safety factor.
+    pub q: Array1<f64>,
+    /// R grid of `psirz` in \[*m*\].
+    pub r: Array1<f64>,
+    /// Z grid of `psirz` in \[*m*\].
+    pub z: Array1<f64>,
+    /// Poloidal flux on the (R, Z) grid, with shape (Z, R).
+    pub psirz: Array2<f64>,
+}
+
+impl ImasEquilibrium {
+    /// Reads the `index`-th time slice of the equilibrium IDS of an IMAS netCDF file, or of an
+    /// HDF5 file written by the Access Layer.
+    ///
+    /// The convention follows the IDS's Data Dictionary version, from its
+    /// `data_dictionary_version` attribute or its `ids_properties.version_put.data_dictionary`:
+    /// COCOS 17 for version 4 and later, and COCOS 11 otherwise.
+    pub fn open(path: &Path, index: usize) -> Result<Self> {
+        Ok(Self::read(&open_file(path)?, index, None)?.0)
+    }
+
+    /// Reads the `index`-th time slice of the equilibrium IDS of an IMAS netCDF or Access Layer
+    /// HDF5 file, whose quantities follow `cocos` regardless of the Data Dictionary version.
+    pub fn open_with_cocos(path: &Path, index: usize, cocos: Cocos) -> Result<Self> {
+        Ok(Self::read(&open_file(path)?, index, Some(cocos))?.0)
+    }
+
+    /// Reads the `index`-th time slice, and the IDS's Data Dictionary version if recorded.
+    fn read(file: &NcFile, index: usize, cocos: Option<Cocos>) -> Result<(Self, Option<Box<str>>)> {
+        let ids = NcSource::new(file).in_group(IDS)?;
+        let (layout, time_dim) = Layout::detect(&ids)?;
+        let dd_version = layout.dd_version(&ids);
+        let cocos = match cocos {
+            Some(cocos) => cocos,
+            None => dd_cocos(dd_version.as_deref()),
+        };
+        let f = NcSource::new(file).in_group(IDS)?.at_time(&time_dim, index);
+        let name = |path: &str| layout.name(path);
+
+        let b0 = extract_1d_var::<f64>(&ids, &name("vacuum_toroidal_field.b0"))?;
+        let b0 = match b0.get(index) {
+            Some(&b0) => b0,
+            None => {
+                return Err(NcError::TimeIndexOutOfRange {
+                    index,
+                    len: b0.len(),
+                });
+            }
+        };
+
+        // The first profiles_2d on a rectangular grid.
+        let grid_types =
+            extract_1d_var::<i32>(&f, &name("time_slice.profiles_2d.grid_type.index"))?;
+        let k = match grid_types.iter().position(|&t| t == RECTANGULAR) {
+            Some(k) => k,
+            None => return Err(imas_error("no profiles_2d on a rectangular grid")),
+        };
+        let r: Array1<f64> = profile_2d(&f, layout, "time_slice.profiles_2d.grid.dim1", k)?;
+        let z: Array1<f64> = profile_2d(&f, layout, "time_slice.profiles_2d.grid.dim2", k)?;
+        let psi_2d: Array2<f64> = profile_2d(&f, layout, "time_slice.profiles_2d.psi", k)?;
+        check_shape(
+            "time_slice.profiles_2d.psi",
+            &[r.len(), z.len()],
+            psi_2d.shape(),
+        )?;
+
+        let psi = profile_1d(&f, layout, "time_slice.profiles_1d.psi")?;
+        let profile = |path: &str| {
+            let values = profile_1d(&f, layout, path)?;
+            check_shape(path, psi.shape(), values.shape())?;
+            Ok::<_, NcError>(values)
+        };
+        let q = profile("time_slice.profiles_1d.q")?;
+        let global = |quantity: &str| {
+            extract_scalar(
+                &f,
+                &name(&format!("time_slice.global_quantities.{quantity}")),
+            )
+        };
+
+        let equilibrium = Self {
+            cocos,
+            time: extract_scalar(&f, &name("time_slice.time"))?,
+            r0: extract_scalar(&ids, &name("vacuum_toroidal_field.r0"))?,
+            b0,
+            rmaxis: global("magnetic_axis.r")?,
+            zmaxis: global("magnetic_axis.z")?,
+            psi_axis: global("psi_axis")?,
+            psi_boundary: global("psi_boundary")?,
+            ip: global("ip")?,
+            f: profile("time_slice.profiles_1d.f")?,
+            psi,
+            q,
+            r,
+            z,
+            // IMAS stores the flux with shape (R, Z).
+            psirz: psi_2d.t().as_standard_layout().into_owned(),
+        };
+        Ok((equilibrium, dd_version))
+    }
+
+    /// Returns the same equilibrium in the convention `cocos`.
+    pub fn to_cocos(&self, cocos: Cocos) -> Self {
+        let psi_factor = self.cocos.psi_factor(cocos);
+        let toroidal_factor = self.cocos.toroidal_factor(cocos);
+        Self {
+            cocos,
+            time: self.time,
+            r0: self.r0,
+            b0: toroidal_factor * self.b0,
+            rmaxis: self.rmaxis,
+            zmaxis: self.zmaxis,
+            psi_axis: psi_factor * self.psi_axis,
+            psi_boundary: psi_factor * self.psi_boundary,
+            ip: toroidal_factor * self.ip,
+            psi: psi_factor * &self.psi,
+            f: toroidal_factor * &self.f,
+            q: self.cocos.q_factor(cocos) * &self.q,
+            r: self.r.clone(),
+            z: self.z.clone(),
+            psirz: psi_factor * &self.psirz,
+        }
+    }
+
+    /// Maps the equilibrium to Boozer coordinates on `grid`, creating an `NcData` equivalent to
+    /// opening a NetCDF file with the default [`NcSchema`], like [`Eqdsk::to_nc_data`] does.
+    ///
+    /// The equilibrium is converted to the G-file's convention first, so that the flux is per
+    /// radian, and the 1D profiles are interpolated onto a uniform flux grid.
+    ///
+    /// The returned `NcData`'s path is empty.
+    ///
+    /// [`Eqdsk::to_nc_data`]: crate::Eqdsk::to_nc_data
+    pub fn to_nc_data(&self, grid: &BoozerGrid) -> Result<NcData> {
+        let eq = self.to_cocos(Cocos::EQDSK);
+        let psi_n = (&eq.psi - eq.psi_axis) / (eq.psi_boundary - eq.psi_axis);
+        if !psi_n.windows(2).into_iter().all(|w| w[1] > w[0]) {
+            return Err(NcError::NonMonotonic("time_slice.profiles_1d.psi".into()));
+        }
+
+        let len = psi_n.len().max(2);
+        let uniform = |values: &Array1<f64>| {
+            Array1::from_shape_fn(len, |k| {
+                let weights = linear_weights(psi_n.view(), k as f64 / (len - 1) as f64);
+                weights.iter().map(|&(i, w)| w * values[i]).sum()
+            })
+        };
+        let flux_map = FluxMap {
+            r: eq.r.clone(),
+            z: eq.z.clone(),
+            psi: eq.psirz.clone(),
+            axis: (eq.rmaxis, eq.zmaxis),
+            psi_axis: eq.psi_axis,
+            psi_boundary: eq.psi_boundary,
+            f: uniform(&eq.f),
+            q: uniform(&eq.q),
+        };
+        let mut rec = flux_map
+            .to_raw(grid)?
+            .into_nc_data(PathBuf::new(), &NcSchema::default())?;
+        rec.time = Some(self.time);
+        Ok(rec)
+    }
+}
+
+impl NcData {
+    /// Creates an NcData from the `index`-th time slice of the equilibrium IDS of an IMAS netCDF
+    /// or Access Layer HDF5 file, by mapping it to Boozer coordinates on `grid`. See
+    /// [`ImasEquilibrium::open`] and [`ImasEquilibrium::to_nc_data`].
+    pub fn open_imas(path: PathBuf, index: usize, grid: &BoozerGrid) -> Result<Self> {
+        let file = open_file(&path)?;
+        let (equilibrium, dd_version) = ImasEquilibrium::read(&file, index, None)?;
+        let mut rec = equilibrium.to_nc_data(grid)?;
+        let ids = NcSource::new(&file).in_group(IDS)?;
+        rec.metadata.attributes = extract_global_attributes(&ids);
+        if let Some(version) = dd_version {
+            rec.metadata
+                .attributes
+                .entry("data_dictionary_version".into())
+                .or_insert(AttributeValue::Str(version.into()));
+        }
+        rec.metadata.checksum = Some(checksum(&path)?);
+        rec.path = path;
+        Ok(rec)
+    }
+}
+
+impl std::fmt::Debug for ImasEquilibrium {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("ImasEquilibrium")
+            .field("cocos", &self.cocos)
+            .field("time", &self.time)
+            .field("r0", &self.r0)
+            .field("b0", &self.b0)
+            .field("rmaxis", &self.rmaxis)
+            .field("zmaxis", &self.zmaxis)
+            .field("psi_axis", &self.psi_axis)
+            .field("psi_boundary", &self.psi_boundary)
+            .field("ip", &self.ip)
+            .field("psi_len", &self.psi.len())
+            .field("rz_shape", &self.psirz.shape())
+            .finish()
+    }
+}
+
+fn imas_error(reason: &str) -> NcError {
+    NcError::ImasFormat(reason.into())
+}
+
+/// Returns the convention of a Data Dictionary version.
+fn dd_cocos(version: Option<&str>) -> Cocos {
+    let major = version.and_then(|v| v.split('.').next()?.trim().parse::<u32>().ok());
+    match major {
+        Some(4..) => Cocos::IMAS_DD4,
+        _ => Cocos::IMAS_DD3,
+    }
+}
+
+/// Extracts a 1D profile of the source's time slice, without its padding.
+fn profile_1d(f: &NcSource, layout: Layout, path: &str) -> Result<Array1<f64>> {
+    let values: Array1<f64> = extract_1d_var(f, &layout.name(path))?;
+    let len = match layout.sizes(f, path)? {
+        Some(sizes) => sizes
+            .iter()
+            .next()
+            .map_or(values.len(), |&len| len as usize),
+        None => values.len(),
+    };
+    let len = len.min(values.len());
+    Ok(values.slice_move(s![..len]))
+}
+
+/// Extracts the `k`-th `profiles_2d` entry of a quantity of the source's time slice, without its
+/// padding.
+fn profile_2d<D: Dimension>(
+    f: &NcSource,
+    layout: Layout,
+    path: &str,
+    k: usize,
+) -> Result<Array<f64, D>> {
+    let name = layout.name(path);
+    let values: ArrayD<f64> = extract_nd_var(f, &name)?;
+    if values.ndim() == 0 || k >= values.len_of(Axis(0)) {
+        return Err(imas_error(&format!(
+            "'{name}' has no profiles_2d entry {k}"
+        )));
+    }
+    let mut entry = values.index_axis_move(Axis(0), k);
+    if let Some(sizes) = layout.sizes(f, path)? {
+        let sizes: Vec<usize> = match sizes.ndim() {
+            2 if k < sizes.len_of(Axis(0)) => sizes
+                .index_axis(Axis(0), k)
+                .iter()
+                .map(|&n| n as usize)
+                .collect(),
+            _ => vec![],
+        };
+        if sizes.len() != entry.ndim() {
+            return Err(imas_error(&format!(
+                "'{name}_SHAPE' does not match '{name}'"
+            )));
+        }
+        entry = entry
+            .slice_each_axis(|axis| Slice::from(..sizes[axis.axis.index()].min(axis.len)))
+            .to_owned();
+    }
+    entry.into_dimensionality::<D>().map_err(|_| match D::NDIM {
+        Some(1) => NcError::Not1D(name.into()),
+        _ => NcError::Not2D(name.into()),
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_cocos_factors() -> Result<()> {
+        let (dd3, eqdsk) = (Cocos::IMAS_DD3, Cocos::EQDSK);
+        assert_eq!(dd3.psi_factor(eqdsk), 1.0 / TAU);
+        assert_eq!(eqdsk.psi_factor(dd3), TAU);
+        assert_eq!(dd3.q_factor(eqdsk), 1.0);
+        assert_eq!(dd3.toroidal_factor(eqdsk), 1.0);
+
+        // COCOS 17 differs from 11 only in the sign of ψ.
+        assert_eq!(Cocos::IMAS_DD4.psi_factor(dd3), -1.0);
+        assert_eq!(Cocos::IMAS_DD4.q_factor(dd3), 1.0);
+        assert_eq!(Cocos::new(5)?.q_factor(eqdsk), -1.0);
+        // (R, Z, φ) conventions flip the toroidal components, and ψ with the current.
+        assert_eq!(Cocos::new(2)?.toroidal_factor(eqdsk), -1.0);
+        assert_eq!(Cocos::new(2)?.psi_factor(eqdsk), -1.0);
+
+        assert!(matches!(Cocos::new(9), Err(NcError::InvalidCocos(9))));
+        assert!(matches!(Cocos::new(0), Err(NcError::InvalidCocos(0))));
+        Ok(())
+    }
+
+    #[test]
+    fn test_dd_cocos() {
+        assert_eq!(dd_cocos(Some("3.41.0")), Cocos::IMAS_DD3);
+        assert_eq!(dd_cocos(Some("4.0.0")), Cocos::IMAS_DD4);
+        assert_eq!(dd_cocos(None), Cocos::IMAS_DD3);
+    }
+}
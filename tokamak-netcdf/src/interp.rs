@@ -0,0 +1,286 @@
+//! Interpolation schemes of the `Currents` and `Bfield` evaluators.
+
+use crate::spline::{solve_cyclic, solve_tridiagonal};
+
+/// A scheme for interpolating values given on strictly increasing nodes.
+///
+/// The evaluators interpolate each interval between consecutive nodes with the cubic matching
+/// the values on the interval's ends and the slopes the scheme assigns to them. Every scheme
+/// therefore passes through the nodes, and the schemes differ only in the slopes, and in the
+/// smoothness and overshoot between the nodes that the slopes give.
+pub trait Interpolation {
+    /// Returns the slopes on the (left, right) ends of each interval between consecutive nodes
+    /// `x`, for the values `y` on the nodes.
+    ///
+    /// `x` is strictly increasing, and has the same, non-zero length as `y`. With a `period`,
+    /// the values repeat every period, `x` spans less than one, and a last interval closes the
+    /// period, from the last node to the first one plus the period.
+    fn slopes(&self, x: &[f64], y: &[f64], period: Option<f64>) -> Vec<[f64; 2]>;
+}
+
+/// Piecewise linear interpolation. The fastest scheme, whose derivatives are discontinuous on
+/// the nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Linear;
+
+/// Natural cubic spline, or periodic cubic spline if the values are periodic. The smoothest
+/// scheme, with continuous first and second derivatives, which may overshoot near steep
+/// gradients. The second derivative vanishes on the first and last nodes.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Cubic;
+
+/// Akima's scheme, whose slopes are weighted averages of the neighbouring secants. Robust to
+/// outliers and steep gradients, with continuous first derivatives. Near the first and last
+/// nodes, the secants are extrapolated linearly.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Akima;
+
+/// Steffen's monotonic scheme, which never overshoots between the nodes: the interpolant is
+/// monotonic wherever the values are, and has extrema only on the nodes. The first derivatives
+/// are continuous.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct Steffen;
+
+impl Interpolation for Linear {
+    fn slopes(&self, x: &[f64], y: &[f64], period: Option<f64>) -> Vec<[f64; 2]> {
+        let (_, secants) = intervals(x, y, period);
+        secants.into_iter().map(|s| [s, s]).collect()
+    }
+}
+
+impl Interpolation for Cubic {
+    fn slopes(&self, x: &[f64], y: &[f64], period: Option<f64>) -> Vec<[f64; 2]> {
+        let (h, secants) = intervals(x, y, period);
+        let n = x.len();
+
+        // Second derivatives on the nodes, from the continuity of the first derivative.
+        let m = match period {
+            None if n > 2 => {
+                let lower = &h[..n - 2];
+                let diag: Vec<f64> = (1..n - 1).map(|k| 2.0 * (h[k - 1] + h[k])).collect();
+                let upper = &h[1..];
+                let rhs: Vec<f64> = (1..n - 1)
+                    .map(|k| 6.0 * (secants[k] - secants[k - 1]))
+                    .collect();
+                let interior = solve_tridiagonal(lower, &diag, upper, &rhs);
+                [vec![0.0], interior, vec![0.0]].concat()
+            }
+            None => vec![0.0; n],
+            Some(period) => {
+                let previous = |k: usize| (k + n - 1) % n;
+                let rhs: Vec<f64> = (0..n)
+                    .map(|k| 6.0 * (secants[k] - secants[previous(k)]))
+                    .collect();
+                match n {
+                    1 => vec![0.0],
+                    2 => {
+                        // Both neighbours of each node are the other node.
+                        let (diag, off) = (2.0 * period, period);
+                        let det = diag * diag - off * off;
+                        vec![
+                            (diag * rhs[0] - off * rhs[1]) / det,
+                            (diag * rhs[1] - off * rhs[0]) / det,
+                        ]
+                    }
+                    _ => {
+                        let lower: Vec<f64> = (0..n).map(|k| h[previous(k)]).collect();
+                        let diag: Vec<f64> =
+                            (0..n).map(|k| 2.0 * (h[previous(k)] + h[k])).collect();
+                        solve_cyclic(&lower, &diag, &h, &rhs)
+                    }
+                }
+            }
+        };
+
+        (0..h.len())
+            .map(|k| {
+                let (m0, m1) = (m[k], m[(k + 1) % n]);
+                [
+                    secants[k] - h[k] * (2.0 * m0 + m1) / 6.0,
+                    secants[k] + h[k] * (m0 + 2.0 * m1) / 6.0,
+                ]
+            })
+            .collect()
+    }
+}
+
+impl Interpolation for Akima {
+    fn slopes(&self, x: &[f64], y: &[f64], period: Option<f64>) -> Vec<[f64; 2]> {
+        let (_, secants) = intervals(x, y, period);
+        let n = x.len();
+        if secants.len() < 2 && period.is_none() {
+            return Linear.slopes(x, y, period);
+        }
+
+        // Secant of interval k, which may lie before the first or after the last interval.
+        let last = secants.len() as isize - 1;
+        let secant = |k: isize| -> f64 {
+            match period {
+                Some(_) => secants[k.rem_euclid(secants.len() as isize) as usize],
+                None => {
+                    let at = |k: isize| secants[k as usize];
+                    match k {
+                        -2 => 3.0 * at(0) - 2.0 * at(1),
+                        -1 => 2.0 * at(0) - at(1),
+                        k if k == last + 1 => 2.0 * at(last) - at(last - 1),
+                        k if k == last + 2 => 3.0 * at(last) - 2.0 * at(last - 1),
+                        k => at(k),
+                    }
+                }
+            }
+        };
+
+        let node_slopes: Vec<f64> = (0..n as isize)
+            .map(|i| {
+                let (m0, m1, m2, m3) = (secant(i - 2), secant(i - 1), secant(i), secant(i + 1));
+                let (w_left, w_right) = ((m3 - m2).abs(), (m1 - m0).abs());
+                match w_left + w_right {
+                    0.0 => 0.5 * (m1 + m2),
+                    total => (w_left * m1 + w_right * m2) / total,
+                }
+            })
+            .collect();
+        interval_slopes(&node_slopes, secants.len())
+    }
+}
+
+impl Interpolation for Steffen {
+    fn slopes(&self, x: &[f64], y: &[f64], period: Option<f64>) -> Vec<[f64; 2]> {
+        let (h, secants) = intervals(x, y, period);
+        let n = x.len();
+        if secants.len() < 2 && period.is_none() {
+            return Linear.slopes(x, y, period);
+        }
+
+        // Slope on a node between intervals `left` and `right`.
+        let interior = |left: usize, right: usize| {
+            let (s0, s1) = (secants[left], secants[right]);
+            let p = (s0 * h[right] + s1 * h[left]) / (h[left] + h[right]);
+            (sign(s0) + sign(s1)) * s0.abs().min(s1.abs()).min(0.5 * p.abs())
+        };
+        // Slope on an end node, whose interval is `near`, followed by `far`.
+        let end = |near: usize, far: usize| {
+            let ratio = h[near] / (h[near] + h[far]);
+            let p = secants[near] * (1.0 + ratio) - secants[far] * ratio;
+            match p * secants[near] {
+                product if product <= 0.0 => 0.0,
+                _ if p.abs() > 2.0 * secants[near].abs() => 2.0 * secants[near],
+                _ => p,
+            }
+        };
+
+        let node_slopes: Vec<f64> = match period {
+            Some(_) => (0..n).map(|i| interior((i + n - 1) % n, i)).collect(),
+            None => (0..n)
+                .map(|i| match i {
+                    0 => end(0, 1),
+                    i if i == n - 1 => end(n - 2, n - 3),
+                    i => interior(i - 1, i),
+                })
+                .collect(),
+        };
+        interval_slopes(&node_slopes, secants.len())
+    }
+}
+
+/// Returns the widths and the secants of the intervals between consecutive nodes, including the
+/// interval closing the period, if any.
+fn intervals(x: &[f64], y: &[f64], period: Option<f64>) -> (Vec<f64>, Vec<f64>) {
+    let n = x.len();
+    let count = match period {
+        Some(_) => n,
+        None => n - 1,
+    };
+    (0..count)
+        .map(|k| {
+            let (x1, y1) = match k + 1 < n {
+                true => (x[k + 1], y[k + 1]),
+                // Safe unwrap(); only periodic values have an interval past the last node.
+                false => (x[0] + period.unwrap(), y[0]),
+            };
+            let h = x1 - x[k];
+            (h, (y1 - y[k]) / h)
+        })
+        .unzip()
+}
+
+/// Assigns the slopes on the nodes to the ends of the intervals between them. With `count`
+/// equal to the number of nodes, the last interval closes the period.
+fn interval_slopes(node_slopes: &[f64], count: usize) -> Vec<[f64; 2]> {
+    let n = node_slopes.len();
+    (0..count)
+        .map(|k| [node_slopes[k], node_slopes[(k + 1) % n]])
+        .collect()
+}
+
+/// Sign of `x`, which is 0 for 0.
+fn sign(x: f64) -> f64 {
+    match x {
+        0.0 => 0.0,
+        x => x.signum(),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::spline::Hermite;
+    use std::f64::consts::TAU;
+
+    /// Step-like values, where cubic splines overshoot.
+    const X: [f64; 8] = [0.0, 1.0, 2.0, 3.0, 3.5, 4.0, 5.0, 6.0];
+    const Y: [f64; 8] = [0.0, 0.0, 0.0, 0.1, 0.9, 1.0, 1.0, 1.0];
+
+    const SCHEMES: [&dyn Interpolation; 4] = [&Linear, &Cubic, &Akima, &Steffen];
+
+    #[test]
+    fn test_nodes() {
+        for scheme in SCHEMES {
+            let interp = Hermite::new(&X, &Y, scheme);
+            for (&x, &y) in X.iter().zip(&Y) {
+                assert_eq!(interp.eval(x, &mut 0).0, y);
+            }
+            // Lines are reproduced exactly.
+            let line: Vec<f64> = X.iter().map(|x| 2.0 * x - 1.0).collect();
+            let interp = Hermite::new(&X, &line, scheme);
+            let (value, derivative) = interp.eval(2.7, &mut 0);
+            assert!((value - 4.4).abs() < 1e-12 && (derivative - 2.0).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_overshoot() {
+        let points = (0..=600).map(|k| k as f64 / 100.0);
+        let range = |scheme: &dyn Interpolation| {
+            let interp = Hermite::new(&X, &Y, scheme);
+            points
+                .clone()
+                .map(|x| interp.eval(x, &mut 0).0)
+                .fold((0.0, 1.0), |(min, max): (f64, f64), y| {
+                    (min.min(y), max.max(y))
+                })
+        };
+        assert!(range(&Cubic).0 < -0.01);
+        assert_eq!(range(&Steffen), (0.0, 1.0));
+        assert_eq!(range(&Akima), (0.0, 1.0));
+        assert_eq!(range(&Linear), (0.0, 1.0));
+    }
+
+    #[test]
+    fn test_periodic() {
+        let x: [f64; 8] = [0.1, 0.9, 2.0, 2.7, 3.5, 4.6, 5.2, 5.9];
+        let y = x.map(|x| 1.0 + 0.3 * x.cos());
+        let smooth: [&dyn Interpolation; 3] = [&Cubic, &Akima, &Steffen];
+        for scheme in smooth {
+            // One interval per node, and continuous first derivatives on every node, including
+            // the first one.
+            let slopes = scheme.slopes(&x, &y, Some(TAU));
+            assert_eq!(slopes.len(), x.len());
+            for k in 0..x.len() {
+                assert!((slopes[k][1] - slopes[(k + 1) % x.len()][0]).abs() < 1e-12);
+            }
+        }
+        let closing = Linear.slopes(&x, &y, Some(TAU))[7];
+        assert_eq!(closing, [(y[0] - y[7]) / (x[0] + TAU - x[7]); 2]);
+    }
+}
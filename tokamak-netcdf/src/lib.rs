@@ -23,22 +23,76 @@
 //!
 //! [libnetcdf](https://github.com/Unidata/netcdf-c) is linked statically, since it is not
 //! available by default in most systems.
+//!
+//! ## Features
+//!
+//! - `netcdf` (default): reads and writes files through libnetcdf.
+//! - `classic`: adds a pure-Rust reader of the NetCDF-3 classic and 64-bit offset formats. With
+//!   `netcdf` disabled, it is used by all the functions that read NetCDF files, so that the crate
+//!   can be built without the C library. Writing requires `netcdf`.
+
+#[cfg(not(any(feature = "netcdf", feature = "classic")))]
+compile_error!("At least one of the `netcdf` and `classic` features must be enabled.");
 
+mod accel;
+mod analytic;
+mod axis;
+mod backend;
+mod bicubic;
+mod boozer;
+mod boozmn;
+#[cfg(feature = "classic")]
+mod classic;
+mod domain;
+mod eqdsk;
 mod error;
 mod extract;
+mod fourier;
+mod imas;
+mod interp;
 mod open;
+mod raw;
+mod resample;
+mod schema;
+mod slices;
+mod spline;
+mod subset;
+mod validate;
+#[cfg(feature = "netcdf")]
+mod write;
 
 mod bfield;
 mod coords;
 mod currents;
+mod geometry;
+mod metadata;
+mod profiles;
 mod scalars;
 
+pub use accel::Accelerator;
+pub use analytic::CircularTokamak;
+pub use axis::{AxisExtrapolation, AxisSchema};
+pub use bicubic::BfieldSpline;
+pub use boozer::BoozerGrid;
+pub use boozmn::Boozmn;
+pub use domain::DomainPolicy;
+pub use eqdsk::Eqdsk;
 pub use error::NcError;
+pub use fourier::BfieldFourier;
+pub use imas::{Cocos, ImasEquilibrium};
+pub use interp::{Akima, Cubic, Interpolation, Linear, Steffen};
 pub use open::NcData;
+pub use resample::{PsiGrid, ThetaGrid};
+pub use schema::NcSchema;
+pub use slices::TimeSlices;
+pub use validate::ValidationReport;
 
 pub use bfield::Bfield;
 pub use coords::Coords;
-pub use currents::Currents;
+pub use currents::{Currents, CurrentsSpline};
+pub use geometry::Geometry;
+pub use metadata::{AttributeValue, Metadata, VariableMetadata};
+pub use profiles::Profiles;
 pub use scalars::Scalars;
 
 pub type Result<T> = std::result::Result<T, NcError>;
@@ -0,0 +1,261 @@
+//! `Metadata` implementation.
+
+use std::collections::BTreeMap;
+use std::io::Read;
+use std::path::Path;
+use std::str::FromStr;
+
+use sha2::{Digest, Sha256};
+
+use crate::extract::{NcSource, extract_attribute, extract_global_attributes};
+use crate::{NcError, NcSchema, Result};
+
+/// Global attributes read into the provenance fields of [`Metadata`].
+mod global {
+    pub(super) const SHOT: &str = "shot";
+    pub(super) const TIME: &str = "time";
+    pub(super) const CODE: &str = "code";
+    pub(super) const VERSION: &str = "version";
+    pub(super) const DATE: &str = "date";
+}
+
+/// Provenance of the equilibrium, as recorded in its source file's attributes, and the source
+/// file's checksum.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct Metadata {
+    /// Shot number, from the `shot` global attribute.
+    pub shot: Option<i64>,
+    /// Time of the reconstruction, from the `time` global attribute.
+    pub time: Option<f64>,
+    /// Reconstruction code, from the `code` global attribute.
+    pub code: Option<Box<str>>,
+    /// Reconstruction code's version, from the `version` global attribute.
+    pub version: Option<Box<str>>,
+    /// Creation date, from the `date` global attribute.
+    pub date: Option<Box<str>>,
+    /// All global attributes, with the type they are stored as, and written back as.
+    pub attributes: BTreeMap<Box<str>, AttributeValue>,
+    /// `units` and `long_name` attributes of the equilibrium's variables, by variable name.
+    pub variables: BTreeMap<Box<str>, VariableMetadata>,
+    /// SHA-256 checksum of the source file, as a lowercase hex string.
+    pub checksum: Option<Box<str>>,
+}
+
+/// Value of an attribute, with its NetCDF type, mirroring `netcdf::AttributeValue`. Single
+/// values and arrays are distinct variants.
+#[derive(Debug, Clone, PartialEq)]
+pub enum AttributeValue {
+    Uchar(u8),
+    Uchars(Vec<u8>),
+    Schar(i8),
+    Schars(Vec<i8>),
+    Ushort(u16),
+    Ushorts(Vec<u16>),
+    Short(i16),
+    Shorts(Vec<i16>),
+    Uint(u32),
+    Uints(Vec<u32>),
+    Int(i32),
+    Ints(Vec<i32>),
+    Ulonglong(u64),
+    Ulonglongs(Vec<u64>),
+    Longlong(i64),
+    Longlongs(Vec<i64>),
+    Float(f32),
+    Floats(Vec<f32>),
+    Double(f64),
+    Doubles(Vec<f64>),
+    Str(String),
+    Strs(Vec<String>),
+}
+
+/// Documentation attributes of a single variable.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct VariableMetadata {
+    /// The variable's `units` attribute.
+    pub units: Option<Box<str>>,
+    /// The variable's `long_name` attribute.
+    pub long_name: Option<Box<str>>,
+}
+
+impl Metadata {
+    /// Creates a `Metadata` from the NetCDF file's global attributes and the attributes of the
+    /// variables named in `schema`. The checksum is left empty.
+    pub(crate) fn build(f: &NcSource, schema: &NcSchema) -> Self {
+        let attributes = extract_global_attributes(f);
+        let text = |name: &str| Some(attributes.get(name)?.to_text().into());
+
+        let names = [
+            &schema.psi,
+            &schema.theta,
+            &schema.b_field,
+            &schema.g,
+            &schema.i,
+            &schema.baxis,
+            &schema.raxis,
+            &schema.q,
+            &schema.psip,
+            &schema.r,
+            &schema.z,
+            &schema.time,
+        ];
+        let variables = names
+            .into_iter()
+            .filter_map(|name| {
+                let units = extract_attribute(f, name, "units");
+                let long_name = extract_attribute(f, name, "long_name");
+                match (&units, &long_name) {
+                    (None, None) => None,
+                    _ => Some((name.clone(), VariableMetadata { units, long_name })),
+                }
+            })
+            .collect();
+
+        Self {
+            shot: parse_attribute(&attributes, global::SHOT),
+            time: parse_attribute(&attributes, global::TIME),
+            code: text(global::CODE),
+            version: text(global::VERSION),
+            date: text(global::DATE),
+            variables,
+            checksum: None,
+            attributes,
+        }
+    }
+
+    /// Returns the `units` attribute of the variable `name`, if present.
+    pub fn units(&self, name: &str) -> Option<&str> {
+        self.variables.get(name)?.units.as_deref()
+    }
+}
+
+impl AttributeValue {
+    /// Formats the value as a string. Array values are joined with ", ".
+    pub fn to_text(&self) -> String {
+        fn join<T: ToString>(values: &[T]) -> String {
+            values
+                .iter()
+                .map(T::to_string)
+                .collect::<Vec<_>>()
+                .join(", ")
+        }
+
+        match self {
+            Self::Uchar(x) => x.to_string(),
+            Self::Schar(x) => x.to_string(),
+            Self::Ushort(x) => x.to_string(),
+            Self::Short(x) => x.to_string(),
+            Self::Uint(x) => x.to_string(),
+            Self::Int(x) => x.to_string(),
+            Self::Ulonglong(x) => x.to_string(),
+            Self::Longlong(x) => x.to_string(),
+            Self::Float(x) => x.to_string(),
+            Self::Double(x) => x.to_string(),
+            Self::Str(x) => x.clone(),
+            Self::Uchars(x) => join(x),
+            Self::Schars(x) => join(x),
+            Self::Ushorts(x) => join(x),
+            Self::Shorts(x) => join(x),
+            Self::Uints(x) => join(x),
+            Self::Ints(x) => join(x),
+            Self::Ulonglongs(x) => join(x),
+            Self::Longlongs(x) => join(x),
+            Self::Floats(x) => join(x),
+            Self::Doubles(x) => join(x),
+            Self::Strs(x) => x.join(", "),
+        }
+    }
+
+    /// Returns a numeric value as `f64`s. Returns `None` for strings.
+    pub(crate) fn to_numbers(&self) -> Option<Vec<f64>> {
+        fn convert<T: Copy + Into<f64>>(values: &[T]) -> Vec<f64> {
+            values.iter().map(|&x| x.into()).collect()
+        }
+
+        let values = match self {
+            Self::Uchar(x) => vec![(*x).into()],
+            Self::Schar(x) => vec![(*x).into()],
+            Self::Ushort(x) => vec![(*x).into()],
+            Self::Short(x) => vec![(*x).into()],
+            Self::Uint(x) => vec![(*x).into()],
+            Self::Int(x) => vec![(*x).into()],
+            Self::Ulonglong(x) => vec![*x as f64],
+            Self::Longlong(x) => vec![*x as f64],
+            Self::Float(x) => vec![(*x).into()],
+            Self::Double(x) => vec![*x],
+            Self::Uchars(x) => convert(x),
+            Self::Schars(x) => convert(x),
+            Self::Ushorts(x) => convert(x),
+            Self::Shorts(x) => convert(x),
+            Self::Uints(x) => convert(x),
+            Self::Ints(x) => convert(x),
+            Self::Ulonglongs(x) => x.iter().map(|&x| x as f64).collect(),
+            Self::Longlongs(x) => x.iter().map(|&x| x as f64).collect(),
+            Self::Floats(x) => convert(x),
+            Self::Doubles(x) => x.clone(),
+            Self::Str(_) | Self::Strs(_) => return None,
+        };
+        Some(values)
+    }
+}
+
+/// Parses a global attribute as a number, whether it is stored as one or as a string. Integers
+/// are parsed from their exact decimal representation.
+fn parse_attribute<T: FromStr>(
+    attributes: &BTreeMap<Box<str>, AttributeValue>,
+    name: &str,
+) -> Option<T> {
+    attributes.get(name)?.to_text().trim().parse().ok()
+}
+
+/// Calculates the SHA-256 checksum of a file, as a lowercase hex string.
+pub(crate) fn checksum(path: &Path) -> Result<Box<str>> {
+    let read_error = |source| NcError::ReadError {
+        source,
+        path: path.into(),
+    };
+
+    let mut file = std::fs::File::open(path).map_err(read_error)?;
+    let mut hasher = Sha256::new();
+    let mut buffer = vec![0; 1 << 16];
+    loop {
+        match file.read(&mut buffer).map_err(read_error)? {
+            0 => break,
+            n => hasher.update(&buffer[..n]),
+        }
+    }
+
+    Ok(hex_digest(hasher))
+}
+
+/// Calculates the SHA-256 checksum of a file's contents, as a lowercase hex string.
+pub(crate) fn checksum_bytes(bytes: &[u8]) -> Box<str> {
+    hex_digest(Sha256::new_with_prefix(bytes))
+}
+
+fn hex_digest(hasher: Sha256) -> Box<str> {
+    let hex: String = hasher
+        .finalize()
+        .iter()
+        .map(|byte| format!("{byte:02x}"))
+        .collect();
+    hex.into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_checksum() -> Result<()> {
+        let path = std::env::temp_dir().join(format!("checksum_{}.txt", std::process::id()));
+        std::fs::write(&path, "abc").unwrap();
+        assert_eq!(
+            &*checksum(&path)?,
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+        assert_eq!(checksum(&path)?, checksum_bytes(b"abc"));
+        std::fs::remove_file(path).unwrap();
+        Ok(())
+    }
+}
@@ -1,17 +1,26 @@
 //! Handles NetCDF file opening and `NcData` creation.
 
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
+use crate::backend::NcFile;
 use crate::bfield::Bfield;
+#[cfg(feature = "classic")]
+use crate::classic::ClassicFile;
 use crate::coords::Coords;
 use crate::currents::Currents;
+use crate::extract::NcSource;
+use crate::geometry::Geometry;
+use crate::metadata::{Metadata, checksum, checksum_bytes};
+use crate::profiles::Profiles;
 use crate::scalars::Scalars;
-use crate::{NcError, Result};
+use crate::{NcError, NcSchema, Result};
 
 /// NetCDF equilibrium data.
 pub struct NcData {
     /// Path to NetCDF file.
     pub path: PathBuf,
+    /// Variable names the file was opened with.
+    pub schema: NcSchema,
     /// Equilibrium's scalar values.
     pub scalars: Scalars,
     /// Equilibrium's coordinate variables.
@@ -20,45 +29,143 @@ pub struct NcData {
     pub currents: Currents,
     /// Magnetic field strength.
     pub bfield: Bfield,
+    /// Safety factor and poloidal flux profiles, if present in the file.
+    pub profiles: Option<Profiles>,
+    /// Cylindrical R(ψ, θ) and Z(ψ, θ) maps, if present in the file.
+    pub geometry: Option<Geometry>,
+    /// Time of the slice, if opened from a time-sliced file.
+    pub time: Option<f64>,
+    /// Provenance attributes and checksum of the source file.
+    pub metadata: Metadata,
 }
 
 impl NcData {
-    /// Creates an NcData from a NetCDF file.
+    /// Creates an NcData from a NetCDF file, using the default [`NcSchema`].
     pub fn open(path: PathBuf) -> Result<Self> {
-        use NcError::*;
+        Self::open_with(path, &NcSchema::default())
+    }
+
+    /// Creates an NcData from a NetCDF file, looking up each variable by the name given in
+    /// `schema`.
+    pub fn open_with(path: PathBuf, schema: &NcSchema) -> Result<Self> {
+        let nc_file = open_file(&path)?;
+        let source = NcSource::new(&nc_file).in_group(&schema.group)?;
+        let mut rec = Self::build(path, &source, schema)?;
+        rec.metadata.checksum = Some(checksum(&rec.path)?);
+        Ok(rec)
+    }
+
+    /// Creates an NcData from the equilibrium stored in the `group` of a NetCDF file, using the
+    /// default [`NcSchema`] otherwise. Files may hold several equilibria, each in its own group.
+    pub fn open_group(path: PathBuf, group: &str) -> Result<Self> {
+        Self::open_with(path, &NcSchema::default().with_group(group))
+    }
+
+    /// Creates an NcData from the contents of a NetCDF file held in memory, using the default
+    /// [`NcSchema`]. The returned `NcData`'s path is empty.
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self> {
+        Self::from_bytes_with(bytes, &NcSchema::default())
+    }
 
+    /// Creates an NcData from the contents of a NetCDF file held in memory, looking up each
+    /// variable by the name given in `schema`. The returned `NcData`'s path is empty.
+    pub fn from_bytes_with(bytes: &[u8], schema: &NcSchema) -> Result<Self> {
+        let nc_file = open_bytes(bytes)?;
+        let source = NcSource::new(&nc_file).in_group(&schema.group)?;
+        let mut rec = Self::build(PathBuf::new(), &source, schema)?;
+        rec.metadata.checksum = Some(checksum_bytes(bytes));
+        Ok(rec)
+    }
+
+    /// Creates an NcData from a NetCDF classic or 64-bit offset file, using the pure-Rust reader
+    /// and the default [`NcSchema`].
+    #[cfg(feature = "classic")]
+    pub fn open_classic(path: PathBuf) -> Result<Self> {
+        Self::open_classic_with(path, &NcSchema::default())
+    }
+
+    /// Creates an NcData from a NetCDF classic or 64-bit offset file, using the pure-Rust reader
+    /// and looking up each variable by the name given in `schema`.
+    #[cfg(feature = "classic")]
+    pub fn open_classic_with(path: PathBuf, schema: &NcSchema) -> Result<Self> {
         if !path.exists() {
-            return Err(FileNotFound(path));
+            return Err(NcError::FileNotFound(path));
         }
+        let nc_file = NcFile::Classic(ClassicFile::open(&path)?);
+        let source = NcSource::new(&nc_file).in_group(&schema.group)?;
+        let mut rec = Self::build(path, &source, schema)?;
+        rec.metadata.checksum = Some(checksum(&rec.path)?);
+        Ok(rec)
+    }
 
-        // If this fails, its due to an underlying library error.
-        let nc_file = match netcdf::open(&path) {
-            Ok(nc_file) => nc_file,
-            Err(liberror) => {
-                return Err(LibraryError {
-                    source: liberror, // Error::Netcdf
-                    reason: "Error opening NetCDF file".into(),
-                });
-            }
-        };
-
-        let scalars = Scalars::build(&nc_file)?;
-        let coords = Coords::build(&nc_file)?;
-        let currents = Currents::build(&nc_file)?;
-        let bfield = Bfield::build(&nc_file)?;
+    /// Creates an NcData from the variables of `f`, without the source file's checksum.
+    pub(crate) fn build(path: PathBuf, f: &NcSource, schema: &NcSchema) -> Result<Self> {
+        let (coords, file_psi) = Coords::build(f, schema)?;
+        let file_psi = file_psi.view();
+        let scalars = Scalars::build(f, schema, file_psi)?;
+        let currents = Currents::build(f, schema, coords.psi.view(), file_psi)?;
+        let bfield = Bfield::build(f, schema, file_psi)?;
+        let profiles = Profiles::build(f, schema, file_psi)?;
+        let geometry = Geometry::build(f, schema, file_psi)?;
+        let metadata = Metadata::build(f, schema);
 
         let rec = NcData {
             path,
+            schema: schema.clone(),
             scalars,
             coords,
             currents,
             bfield,
+            profiles,
+            geometry,
+            time: None,
+            metadata,
         };
 
+        // Report the first mismatch, if any.
+        if let Some(err) = rec.shape_errors().into_iter().next() {
+            return Err(err);
+        }
         Ok(rec)
     }
 }
 
+/// Opens a NetCDF file for reading, with libnetcdf if the `netcdf` feature is enabled and the
+/// pure-Rust classic format reader otherwise.
+pub(crate) fn open_file(path: &Path) -> Result<NcFile<'static>> {
+    use NcError::*;
+
+    if !path.exists() {
+        return Err(FileNotFound(path.into()));
+    }
+
+    // If this fails, its due to an underlying library error.
+    #[cfg(feature = "netcdf")]
+    match netcdf::open(path) {
+        Ok(nc_file) => Ok(NcFile::Netcdf(nc_file)),
+        Err(liberror) => Err(LibraryError {
+            source: liberror, // Error::Netcdf
+            reason: "Error opening NetCDF file".into(),
+        }),
+    }
+    #[cfg(not(feature = "netcdf"))]
+    Ok(NcFile::Classic(ClassicFile::open(path)?))
+}
+
+/// Opens the contents of a NetCDF file held in memory, with the same reader as [`open_file`].
+fn open_bytes(bytes: &[u8]) -> Result<NcFile<'_>> {
+    #[cfg(feature = "netcdf")]
+    match netcdf::open_mem(None, bytes) {
+        Ok(nc_file) => Ok(NcFile::Memory(nc_file)),
+        Err(liberror) => Err(NcError::LibraryError {
+            source: liberror,
+            reason: "Error opening NetCDF file from memory".into(),
+        }),
+    }
+    #[cfg(not(feature = "netcdf"))]
+    Ok(NcFile::Classic(ClassicFile::parse(bytes.into())?))
+}
+
 impl std::fmt::Debug for NcData {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         f.debug_struct("NcData")
@@ -66,6 +173,10 @@ impl std::fmt::Debug for NcData {
             .field("coords", &self.coords)
             .field("currents", &self.currents)
             .field("bfield", &self.bfield)
+            .field("profiles", &self.profiles)
+            .field("geometry", &self.geometry)
+            .field("time", &self.time)
+            .field("metadata", &self.metadata)
             .finish()
     }
 }
@@ -74,10 +185,18 @@ impl std::fmt::Debug for NcData {
 mod test {
     use crate::NcData;
     use crate::NcError;
+    use crate::NcSchema;
 
     #[test]
     fn file_not_found() {
         let x: Result<NcData, NcError> = NcData::open("not_an_existing_path".into());
         assert!(matches!(x.unwrap_err(), NcError::FileNotFound { .. }));
     }
+
+    #[test]
+    fn file_not_found_with_schema() {
+        let schema = NcSchema::default().with_psi("psi_tor");
+        let x: Result<NcData, NcError> = NcData::open_with("not_an_existing_path".into(), &schema);
+        assert!(matches!(x.unwrap_err(), NcError::FileNotFound { .. }));
+    }
 }
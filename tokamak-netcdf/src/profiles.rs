@@ -0,0 +1,67 @@
+//! `Profiles` implementation.
+
+use ndarray::{Array1, ArrayView1};
+
+use crate::{
+    NcSchema, Result,
+    extract::{NcSource, extract_1d_var_with_axis, optional},
+};
+
+/// Representation of the equilibrium's optional flux-surface profiles. Each profile is only
+/// present if the corresponding variable exists in the NetCDF file.
+pub struct Profiles {
+    /// Safety factor **q**.
+    pub q: Option<Array1<f64>>,
+    /// Poloidal flux **ψp**.
+    pub psip: Option<Array1<f64>>,
+}
+
+impl Profiles {
+    /// Creates a `Profiles` containing whichever of **q** and **ψp** exist in the NetCDF file.
+    /// Returns `None` if neither does.
+    pub(crate) fn build(
+        f: &NcSource,
+        schema: &NcSchema,
+        file_psi: ArrayView1<f64>,
+    ) -> Result<Option<Self>> {
+        let q = optional(extract_1d_var_with_axis(
+            f,
+            &schema.q,
+            file_psi,
+            schema.axis.q,
+        ))?;
+        let psip = optional(extract_1d_var_with_axis(
+            f,
+            &schema.psip,
+            file_psi,
+            schema.axis.psip,
+        ))?;
+
+        if q.is_none() && psip.is_none() {
+            return Ok(None);
+        }
+
+        Ok(Some(Profiles { q, psip }))
+    }
+}
+
+impl std::fmt::Debug for Profiles {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        writeln!(f, "Profiles: {{")?;
+        for (name, profile) in [("q", &self.q), ("psip", &self.psip)] {
+            match profile {
+                // Safe unwrap(); extracted variables are never empty.
+                Some(arr) => writeln!(
+                    f,
+                    "    {:>5} = [{:.7}, ..., {:.7}], len = {},",
+                    name,
+                    arr.first().unwrap(),
+                    arr.last().unwrap(),
+                    arr.len(),
+                )?,
+                None => writeln!(f, "    {:>5} = None,", name)?,
+            }
+        }
+        write!(f, "}}")
+    }
+}
@@ -0,0 +1,106 @@
+//! Equilibrium data from sources other than the crate's NetCDF layout.
+
+use std::path::PathBuf;
+
+use ndarray::{Array1, Array2};
+
+use crate::axis::{prepend_axis_row, prepend_axis_value};
+use crate::{
+    AxisExtrapolation, Bfield, Coords, Currents, Geometry, Metadata, NcData, NcError, NcSchema,
+    Profiles, Result, Scalars,
+};
+
+/// An equilibrium's quantities on the ψ grid of its source, without any axis values, as they
+/// would be stored in a NetCDF file `NcData::open` reads.
+pub(crate) struct RawEquilibrium {
+    /// Magnetic field strength on the axis in \[*T*\].
+    pub(crate) baxis: f64,
+    /// Tokamak's major radius in \[*m*\].
+    pub(crate) raxis: f64,
+    pub(crate) psi: Array1<f64>,
+    pub(crate) theta: Array1<f64>,
+    pub(crate) i: Array1<f64>,
+    pub(crate) g: Array1<f64>,
+    /// Magnetic field strength, with shape (ψ, θ).
+    pub(crate) b: Array2<f64>,
+    pub(crate) q: Option<Array1<f64>>,
+    pub(crate) psip: Option<Array1<f64>>,
+    /// R(ψ, θ) and Z(ψ, θ) maps.
+    pub(crate) rz: Option<(Array2<f64>, Array2<f64>)>,
+}
+
+impl RawEquilibrium {
+    /// Creates an `NcData`, extrapolating each quantity to the axis exactly like
+    /// [`NcData::open_with`] does with `schema`.
+    pub(crate) fn into_nc_data(self, path: PathBuf, schema: &NcSchema) -> Result<NcData> {
+        if self.psi.is_empty() || self.theta.is_empty() {
+            let name = match self.psi.is_empty() {
+                true => &schema.psi,
+                false => &schema.theta,
+            };
+            return Err(NcError::EmptyVariable(name.clone()));
+        }
+
+        let axis = &schema.axis;
+        let psi = self.psi.view();
+        let with_axis = |name: &str, arr: Array1<f64>, method: AxisExtrapolation| {
+            prepend_axis_value(name, psi, arr, method)
+        };
+        let with_axis_row = |name: &str, arr: Array2<f64>, method: AxisExtrapolation| {
+            prepend_axis_row(name, psi, arr, method)
+        };
+
+        // Safe unwrap(); psi is non-empty.
+        let scalars = Scalars {
+            baxis: self.baxis,
+            raxis: self.raxis,
+            psi_wall: *self.psi.last().unwrap(),
+        };
+        let coord_psi = with_axis(&schema.psi, self.psi.clone(), axis.psi)?;
+        let extrapolated_axis = coord_psi.len() > self.psi.len();
+        let coords = Coords::new(coord_psi, self.theta, extrapolated_axis);
+        let currents = Currents::new(
+            coords.psi.view(),
+            with_axis(&schema.i, self.i, axis.i)?,
+            with_axis(&schema.g, self.g, axis.g)?,
+            schema,
+        )?;
+        let bfield = Bfield::new(with_axis_row(&schema.b_field, self.b, axis.b_field)?);
+
+        let profiles = match (self.q, self.psip) {
+            (None, None) => None,
+            (q, psip) => Some(Profiles {
+                q: q.map(|q| with_axis(&schema.q, q, axis.q)).transpose()?,
+                psip: psip
+                    .map(|psip| with_axis(&schema.psip, psip, axis.psip))
+                    .transpose()?,
+            }),
+        };
+        let geometry = match self.rz {
+            Some((r, z)) => Some(Geometry {
+                r: with_axis_row(&schema.r, r, axis.geometry)?,
+                z: with_axis_row(&schema.z, z, axis.geometry)?,
+            }),
+            None => None,
+        };
+
+        let rec = NcData {
+            path,
+            schema: schema.clone(),
+            scalars,
+            coords,
+            currents,
+            bfield,
+            profiles,
+            geometry,
+            time: None,
+            metadata: Metadata::default(),
+        };
+
+        // Report the first mismatch, if any.
+        if let Some(err) = rec.shape_errors().into_iter().next() {
+            return Err(err);
+        }
+        Ok(rec)
+    }
+}
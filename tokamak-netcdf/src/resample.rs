@@ -0,0 +1,271 @@
+//! Resampling of an `NcData` onto other (ψ, θ) grids.
+
+use std::f64::consts::TAU;
+
+use ndarray::{Array1, Array2, ArrayView1};
+
+use crate::validate::period_len;
+use crate::{
+    AxisSchema, Bfield, Coords, Currents, Geometry, Metadata, NcData, NcError, Profiles, Result,
+    Scalars,
+};
+
+/// Relative tolerance of grid comparisons.
+const TOLERANCE: f64 = 1e-9;
+
+/// ψ grid to resample an equilibrium onto.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PsiGrid {
+    /// Strictly increasing ψ values, within the equilibrium's ψ span.
+    Values(Array1<f64>),
+    /// Points uniform in ψ, spanning the equilibrium's ψ coordinate.
+    Uniform(usize),
+    /// Points uniform in √ψ, spanning the equilibrium's ψ coordinate. Since √ψ is proportional
+    /// to the minor radius, this is roughly uniform in space.
+    UniformSqrt(usize),
+}
+
+/// θ grid to resample an equilibrium onto.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ThetaGrid {
+    /// Strictly increasing θ values. Values outside the equilibrium's θ span are mapped into it
+    /// by periodicity.
+    Values(Array1<f64>),
+    /// Points uniform in \[θ0, θ0 + 2π), where θ0 is the equilibrium's first θ value.
+    Uniform(usize),
+}
+
+/// Linear interpolation weights of a single point: two (index, weight) pairs.
+pub(crate) type Weights = [(usize, f64); 2];
+
+impl PsiGrid {
+    /// Calculates the grid's values, for an equilibrium with ψ coordinate `psi`.
+    fn values(&self, psi: ArrayView1<f64>) -> Result<Array1<f64>> {
+        // Safe unwrap(); coordinates are never empty.
+        let (first, last) = (*psi.first().unwrap(), *psi.last().unwrap());
+        let values = match self {
+            PsiGrid::Values(values) => values.clone(),
+            PsiGrid::Uniform(len) => Array1::linspace(first, last, *len),
+            PsiGrid::UniformSqrt(len) => {
+                Array1::linspace(first.max(0.0).sqrt(), last.sqrt(), *len).mapv(|x| x * x)
+            }
+        };
+        check_grid("psi", values.view())?;
+
+        let tolerance = TOLERANCE * (last - first).abs().max(last.abs());
+        match values
+            .iter()
+            .all(|&p| p >= first - tolerance && p <= last + tolerance)
+        {
+            true => Ok(values),
+            false => Err(NcError::InvalidGrid(
+                format!("psi grid extends beyond [{first}, {last}]").into(),
+            )),
+        }
+    }
+}
+
+impl ThetaGrid {
+    /// Calculates the grid's values, for an equilibrium with θ coordinate `theta`.
+    fn values(&self, theta: ArrayView1<f64>) -> Result<Array1<f64>> {
+        let values = match self {
+            ThetaGrid::Values(values) => values.clone(),
+            ThetaGrid::Uniform(len) => {
+                Array1::from_shape_fn(*len, |j| theta[0] + TAU * j as f64 / *len as f64)
+            }
+        };
+        check_grid("theta", values.view())?;
+        Ok(values)
+    }
+}
+
+/// Checks that a requested grid is non-empty and strictly increasing.
+fn check_grid(name: &str, values: ArrayView1<f64>) -> Result<()> {
+    if values.is_empty() {
+        return Err(NcError::InvalidGrid(format!("{name} grid is empty").into()));
+    }
+    match values.windows(2).into_iter().all(|w| w[1] > w[0]) {
+        true => Ok(()),
+        false => Err(NcError::NonMonotonic(name.into())),
+    }
+}
+
+/// Calculates the linear interpolation weights of `x` on the strictly increasing `nodes`.
+/// Points outside the nodes are extrapolated from the closest interval.
+pub(crate) fn linear_weights(nodes: ArrayView1<f64>, x: f64) -> Weights {
+    let n = nodes.len();
+    if n == 1 {
+        return [(0, 1.0), (0, 0.0)];
+    }
+    let i = nodes
+        .iter()
+        .take_while(|&&node| node <= x)
+        .count()
+        .saturating_sub(1)
+        .min(n - 2);
+    let u = (x - nodes[i]) / (nodes[i + 1] - nodes[i]);
+    [(i, 1.0 - u), (i + 1, u)]
+}
+
+/// Calculates the linear interpolation weights of `x` on the strictly increasing, 2π-periodic
+/// `nodes`. A last node one period after the first is ignored.
+pub(crate) fn periodic_weights(nodes: ArrayView1<f64>, x: f64) -> Weights {
+    let first = nodes[0];
+    let n = period_len(nodes);
+
+    // Close the period with the first node, at index n.
+    let x = first + (x - first).rem_euclid(TAU);
+    let closed = Array1::from_shape_fn(n + 1, |j| match j < n {
+        true => nodes[j],
+        false => first + TAU,
+    });
+    linear_weights(closed.view(), x).map(|(j, w)| (j % n, w))
+}
+
+/// Interpolates a 1D quantity.
+fn interp_1d(values: &Array1<f64>, weights: &[Weights]) -> Array1<f64> {
+    Array1::from_iter(
+        weights
+            .iter()
+            .map(|w| w.iter().map(|&(i, wi)| wi * values[i]).sum()),
+    )
+}
+
+/// Interpolates a 2D (ψ, θ) quantity.
+fn interp_2d(values: &Array2<f64>, psi_w: &[Weights], theta_w: &[Weights]) -> Array2<f64> {
+    Array2::from_shape_fn((psi_w.len(), theta_w.len()), |(k, j)| {
+        let mut sum = 0.0;
+        for &(i, wi) in &psi_w[k] {
+            for &(l, wl) in &theta_w[j] {
+                sum += wi * wl * values[[i, l]];
+            }
+        }
+        sum
+    })
+}
+
+impl NcData {
+    /// Creates a new `NcData` with all quantities linearly interpolated onto the given ψ and θ
+    /// grids. θ is interpolated periodically.
+    ///
+    /// The new `psi_wall` is the last value of the ψ grid. If the equilibrium's ψ starts with an
+    /// extrapolated axis value, the new grid is considered to as well if it starts at the same
+    /// ψ, so that [`NcData::write`] omits it.
+    ///
+    /// ```
+    /// # use tokamak_netcdf::{CircularTokamak, NcError, PsiGrid, ThetaGrid};
+    /// # fn main() -> Result<(), NcError> {
+    /// let nc_data = CircularTokamak::default().to_nc_data()?;
+    /// let resampled = nc_data.resample(&PsiGrid::UniformSqrt(40), &ThetaGrid::Uniform(32))?;
+    /// assert_eq!(resampled.bfield.b.dim(), (40, 32));
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn resample(&self, psi_grid: &PsiGrid, theta_grid: &ThetaGrid) -> Result<NcData> {
+        check_grid(&self.schema.psi, self.coords.psi.view())?;
+        check_grid(&self.schema.theta, self.coords.theta.view())?;
+
+        let psi = psi_grid.values(self.coords.psi.view())?;
+        let theta = theta_grid.values(self.coords.theta.view())?;
+        let psi_weights: Vec<Weights> = psi
+            .iter()
+            .map(|&p| linear_weights(self.coords.psi.view(), p))
+            .collect();
+        let theta_weights: Vec<Weights> = theta
+            .iter()
+            .map(|&t| periodic_weights(self.coords.theta.view(), t))
+            .collect();
+
+        let extrapolated_axis = self.coords.extrapolated_axis && psi[0] == self.coords.psi[0];
+        self.regrid(
+            Coords::new(psi, theta, extrapolated_axis),
+            &psi_weights,
+            &theta_weights,
+        )
+    }
+
+    /// Creates a new `NcData` on the grid of `coords`, computing each quantity's value at a new
+    /// grid point as a weighted sum of its values on the current grid.
+    pub(crate) fn regrid(
+        &self,
+        coords: Coords,
+        psi_weights: &[Weights],
+        theta_weights: &[Weights],
+    ) -> Result<NcData> {
+        let scalars = Scalars {
+            baxis: self.scalars.baxis,
+            raxis: self.scalars.raxis,
+            psi_wall: coords.psi_span.1,
+        };
+        let currents = Currents::new(
+            coords.psi.view(),
+            interp_1d(&self.currents.i, psi_weights),
+            interp_1d(&self.currents.g, psi_weights),
+            &self.schema,
+        )?;
+        let bfield = Bfield::new(interp_2d(&self.bfield.b, psi_weights, theta_weights));
+        let profiles = self.profiles.as_ref().map(|profiles| Profiles {
+            q: profiles.q.as_ref().map(|q| interp_1d(q, psi_weights)),
+            psip: profiles.psip.as_ref().map(|p| interp_1d(p, psi_weights)),
+        });
+        let geometry = self.geometry.as_ref().map(|geometry| Geometry {
+            r: interp_2d(&geometry.r, psi_weights, theta_weights),
+            z: interp_2d(&geometry.z, psi_weights, theta_weights),
+        });
+
+        // Without an axis value, reopening a written file must not extrapolate one.
+        let mut schema = self.schema.clone();
+        if !coords.extrapolated_axis {
+            schema.axis = AxisSchema::none();
+        }
+        // The checksum is the source file's, which the new data no longer matches.
+        let metadata = Metadata {
+            checksum: None,
+            ..self.metadata.clone()
+        };
+
+        Ok(NcData {
+            path: self.path.clone(),
+            schema,
+            scalars,
+            coords,
+            currents,
+            bfield,
+            profiles,
+            geometry,
+            time: self.time,
+            metadata,
+        })
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use ndarray::array;
+    use std::f64::consts::PI;
+
+    #[test]
+    fn test_linear_weights() {
+        let nodes = array![0.0, 1.0, 3.0];
+        assert_eq!(linear_weights(nodes.view(), 2.0), [(1, 0.5), (2, 0.5)]);
+        assert_eq!(linear_weights(nodes.view(), 3.0), [(1, 0.0), (2, 1.0)]);
+        assert_eq!(linear_weights(nodes.view(), 0.0), [(0, 1.0), (1, 0.0)]);
+    }
+
+    #[test]
+    fn test_periodic_weights() {
+        let open = Array1::linspace(0.0, 1.5 * PI, 4);
+        let closed = Array1::linspace(0.0, TAU, 5);
+
+        for nodes in [open, closed] {
+            // Between the last node and 2π, and wrapped around.
+            let [(i, wi), (j, wj)] = periodic_weights(nodes.view(), 1.75 * PI);
+            assert_eq!((i, j), (3, 0));
+            assert!((wi - 0.5).abs() < 1e-12 && (wj - 0.5).abs() < 1e-12);
+            let wrapped = periodic_weights(nodes.view(), -0.25 * PI);
+            assert_eq!(wrapped[0].0, 3);
+            assert!((wrapped[0].1 - 0.5).abs() < 1e-12);
+        }
+    }
+}
@@ -1,6 +1,11 @@
 //! `Scalars` implementation.
 
-use crate::{Result, extract::extract_1d_var, extract::extract_scalar};
+use ndarray::ArrayView1;
+
+use crate::{
+    NcSchema, Result,
+    extract::{NcSource, extract_scalar},
+};
 
 /// Representation of an equilibrium's scalar values. `baxis` and `raxis` are the only quantities
 /// in non-normalized units, and are not used in any calculations.
@@ -9,19 +14,27 @@ pub struct Scalars {
     pub baxis: f64,
     /// Tokamak's major radius in \[*m*\].
     pub raxis: f64,
-    /// Last closed surface \[*Normalised*\].
+    /// Outermost surface of the ψ grid \[*Normalised*\]. This is the last closed surface, unless
+    /// the equilibrium has been restricted with [`NcData::subset_psi`] or resampled.
+    ///
+    /// [`NcData::subset_psi`]: crate::NcData::subset_psi
     pub psi_wall: f64,
 }
 
 impl Scalars {
-    /// Creates a `Scalars` containing the needed scalar values from the NetCDF file.
-    pub(crate) fn build(f: &netcdf::File) -> Result<Self> {
-        let baxis = extract_scalar(f, "Baxis")?;
-        let raxis = extract_scalar(f, "raxis")?;
+    /// Creates a `Scalars` containing the needed scalar values from the NetCDF file, whose ψ
+    /// values are `file_psi`.
+    pub(crate) fn build(
+        f: &NcSource,
+        schema: &NcSchema,
+        file_psi: ArrayView1<f64>,
+    ) -> Result<Self> {
+        let baxis = extract_scalar(f, &schema.baxis)?;
+        let raxis = extract_scalar(f, &schema.raxis)?;
 
         // We can safely assume that the coords are sorted.
         // Whether the variable is empty or not is checked in the extraction.
-        let psi_wall = match extract_1d_var(f, "psi")?.last() {
+        let psi_wall = match file_psi.last() {
             Some(last) => *last,
             None => unreachable!("Error extracting psi_wall."),
         };
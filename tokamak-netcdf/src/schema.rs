@@ -0,0 +1,199 @@
+//! `NcSchema` implementation.
+
+use crate::AxisSchema;
+
+/// Mapping of the equilibrium's quantities to the variable names used in the NetCDF file.
+///
+/// The default schema corresponds to the names used by the reconstruction pipeline the crate was
+/// originally written for. Files with different conventions can be opened by overriding only the
+/// names that differ:
+///
+/// ```
+/// # use tokamak_netcdf::NcSchema;
+/// let schema = NcSchema::default()
+///     .with_psi("psi_tor")
+///     .with_theta("theta_b")
+///     .with_b_field("bmod");
+///
+/// assert_eq!(&*schema.psi, "psi_tor");
+/// assert_eq!(&*schema.g, "g_norm");
+/// ```
+///
+/// Presets cover the other pipelines' conventions, and can be overridden the same way:
+///
+/// ```
+/// # use tokamak_netcdf::NcSchema;
+/// let schema = NcSchema::psi_tor().with_q("q_profile");
+///
+/// assert_eq!(&*schema.b_field, "B");
+/// assert_eq!(NcSchema::psi_tor_bmod(), NcSchema::psi_tor().with_b_field("bmod"));
+/// ```
+///
+/// Variable names may be paths through the file's groups. Names are relative to `group`, unless
+/// they start with '/':
+///
+/// ```
+/// # use tokamak_netcdf::NcSchema;
+/// let schema = NcSchema::default()
+///     .with_group("equilibrium/boozer")
+///     .with_q("/profiles/q");
+/// ```
+#[derive(Debug, Clone, PartialEq)]
+pub struct NcSchema {
+    /// Name of the ψ coordinate variable.
+    pub psi: Box<str>,
+    /// Name of the θ coordinate variable.
+    pub theta: Box<str>,
+    /// Name of the magnetic field strength variable, with shape (ψ, θ).
+    pub b_field: Box<str>,
+    /// Name of the plasma poloidal current **g** variable.
+    pub g: Box<str>,
+    /// Name of the plasma toroidal current **I** variable.
+    pub i: Box<str>,
+    /// Name of the scalar magnetic field strength on the axis.
+    pub baxis: Box<str>,
+    /// Name of the scalar major radius.
+    pub raxis: Box<str>,
+    /// Name of the optional safety factor variable.
+    pub q: Box<str>,
+    /// Name of the optional poloidal flux variable.
+    pub psip: Box<str>,
+    /// Name of the optional cylindrical R(ψ, θ) variable.
+    pub r: Box<str>,
+    /// Name of the optional cylindrical Z(ψ, θ) variable.
+    pub z: Box<str>,
+    /// Name of the time dimension and coordinate variable of time-sliced files.
+    pub time: Box<str>,
+    /// Path of the group holding the equilibrium, such as `equilibrium/boozer`. Variable names
+    /// are relative to it, unless they start with '/'. Empty for the root group.
+    pub group: Box<str>,
+    /// How each quantity is extrapolated to the magnetic axis.
+    pub axis: AxisSchema,
+}
+
+impl NcSchema {
+    /// Creates an `NcSchema` with the default variable names.
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Creates an `NcSchema` for pipelines labelling the surfaces by the toroidal flux, with the
+    /// `psi_tor`, `theta_b` and `B` coordinate and field variables. Other names are the defaults.
+    pub fn psi_tor() -> Self {
+        Self::default()
+            .with_psi("psi_tor")
+            .with_theta("theta_b")
+            .with_b_field("B")
+    }
+
+    /// Creates an `NcSchema` like [`NcSchema::psi_tor`], for pipelines naming the field strength
+    /// `bmod`.
+    pub fn psi_tor_bmod() -> Self {
+        Self::psi_tor().with_b_field("bmod")
+    }
+
+    /// Sets the name of the ψ coordinate variable.
+    pub fn with_psi(mut self, name: &str) -> Self {
+        self.psi = name.into();
+        self
+    }
+
+    /// Sets the name of the θ coordinate variable.
+    pub fn with_theta(mut self, name: &str) -> Self {
+        self.theta = name.into();
+        self
+    }
+
+    /// Sets the name of the magnetic field strength variable.
+    pub fn with_b_field(mut self, name: &str) -> Self {
+        self.b_field = name.into();
+        self
+    }
+
+    /// Sets the name of the plasma poloidal current **g** variable.
+    pub fn with_g(mut self, name: &str) -> Self {
+        self.g = name.into();
+        self
+    }
+
+    /// Sets the name of the plasma toroidal current **I** variable.
+    pub fn with_i(mut self, name: &str) -> Self {
+        self.i = name.into();
+        self
+    }
+
+    /// Sets the name of the magnetic field strength on the axis.
+    pub fn with_baxis(mut self, name: &str) -> Self {
+        self.baxis = name.into();
+        self
+    }
+
+    /// Sets the name of the major radius.
+    pub fn with_raxis(mut self, name: &str) -> Self {
+        self.raxis = name.into();
+        self
+    }
+
+    /// Sets the name of the safety factor variable.
+    pub fn with_q(mut self, name: &str) -> Self {
+        self.q = name.into();
+        self
+    }
+
+    /// Sets the name of the poloidal flux variable.
+    pub fn with_psip(mut self, name: &str) -> Self {
+        self.psip = name.into();
+        self
+    }
+
+    /// Sets the name of the cylindrical R(ψ, θ) variable.
+    pub fn with_r(mut self, name: &str) -> Self {
+        self.r = name.into();
+        self
+    }
+
+    /// Sets the name of the cylindrical Z(ψ, θ) variable.
+    pub fn with_z(mut self, name: &str) -> Self {
+        self.z = name.into();
+        self
+    }
+
+    /// Sets the name of the time dimension and coordinate variable.
+    pub fn with_time(mut self, name: &str) -> Self {
+        self.time = name.into();
+        self
+    }
+
+    /// Sets the path of the group holding the equilibrium.
+    pub fn with_group(mut self, path: &str) -> Self {
+        self.group = path.into();
+        self
+    }
+
+    /// Sets the axis extrapolation of each quantity.
+    pub fn with_axis(mut self, axis: AxisSchema) -> Self {
+        self.axis = axis;
+        self
+    }
+}
+
+impl Default for NcSchema {
+    fn default() -> Self {
+        Self {
+            psi: "psi".into(),
+            theta: "boozer_theta".into(),
+            b_field: "b_field_norm".into(),
+            g: "g_norm".into(),
+            i: "I_norm".into(),
+            baxis: "Baxis".into(),
+            raxis: "raxis".into(),
+            q: "q".into(),
+            psip: "psip".into(),
+            r: "R".into(),
+            z: "Z".into(),
+            time: "time".into(),
+            group: "".into(),
+            axis: AxisSchema::default(),
+        }
+    }
+}
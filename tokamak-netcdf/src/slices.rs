@@ -0,0 +1,134 @@
+//! Time-sliced NetCDF files.
+
+use std::path::PathBuf;
+
+use ndarray::Array1;
+
+use crate::extract::{NcSource, extract_1d_var};
+use crate::metadata::checksum;
+use crate::open::open_file;
+use crate::{NcData, NcError, NcSchema, Result};
+
+/// A NetCDF file containing several time slices of an equilibrium.
+///
+/// Variables whose first dimension is the schema's time dimension are read one slice at a time,
+/// while the rest, typically the ψ and θ coordinates, are shared by all slices.
+///
+/// ```no_run
+/// # use tokamak_netcdf::{NcError, TimeSlices};
+/// # fn main() -> Result<(), NcError> {
+/// let slices = TimeSlices::open("discharge.nc".into())?;
+/// for nc_data in slices.iter() {
+///     let nc_data = nc_data?;
+///     println!("t = {:?}, psi_wall = {}", nc_data.time, nc_data.scalars.psi_wall);
+/// }
+/// let nearest = slices.get_nearest(1.25)?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct TimeSlices {
+    /// Path to NetCDF file.
+    pub path: PathBuf,
+    /// Variable names the file is opened with.
+    pub schema: NcSchema,
+    /// Time of each slice.
+    pub times: Array1<f64>,
+    /// SHA-256 checksum of the file, recorded in each slice's metadata.
+    checksum: Box<str>,
+}
+
+impl TimeSlices {
+    /// Reads the time coordinate of a time-sliced NetCDF file, using the default [`NcSchema`].
+    pub fn open(path: PathBuf) -> Result<Self> {
+        Self::open_with(path, &NcSchema::default())
+    }
+
+    /// Reads the time coordinate of a time-sliced NetCDF file, looking up each variable by the
+    /// name given in `schema`.
+    pub fn open_with(path: PathBuf, schema: &NcSchema) -> Result<Self> {
+        let nc_file = open_file(&path)?;
+        let source = NcSource::new(&nc_file).in_group(&schema.group)?;
+        let times = extract_1d_var(&source, &schema.time)?;
+        Ok(Self {
+            checksum: checksum(&path)?,
+            path,
+            schema: schema.clone(),
+            times,
+        })
+    }
+
+    /// Returns the number of time slices.
+    pub fn len(&self) -> usize {
+        self.times.len()
+    }
+
+    /// Returns `true` if the file contains no time slices.
+    pub fn is_empty(&self) -> bool {
+        self.times.is_empty()
+    }
+
+    /// Returns the index of the slice closest to `time`.
+    pub fn nearest(&self, time: f64) -> usize {
+        let distance = |index: &usize| (self.times[*index] - time).abs();
+        // Safe unwrap(); extracted variables are never empty.
+        (0..self.len())
+            .min_by(|a, b| distance(a).total_cmp(&distance(b)))
+            .unwrap()
+    }
+
+    /// Creates an NcData from the `index`-th time slice.
+    pub fn get(&self, index: usize) -> Result<NcData> {
+        if index >= self.len() {
+            return Err(NcError::TimeIndexOutOfRange {
+                index,
+                len: self.len(),
+            });
+        }
+        let nc_file = open_file(&self.path)?;
+        let source = NcSource::new(&nc_file)
+            .at_time(&self.schema.time, index)
+            .in_group(&self.schema.group)?;
+        let mut rec = NcData::build(self.path.clone(), &source, &self.schema)?;
+        rec.time = Some(self.times[index]);
+        rec.metadata.checksum = Some(self.checksum.clone());
+        Ok(rec)
+    }
+
+    /// Creates an NcData from the time slice closest to `time`.
+    pub fn get_nearest(&self, time: f64) -> Result<NcData> {
+        self.get(self.nearest(time))
+    }
+
+    /// Returns an iterator creating an NcData from each time slice, in order.
+    pub fn iter(&self) -> impl Iterator<Item = Result<NcData>> + '_ {
+        (0..self.len()).map(|index| self.get(index))
+    }
+}
+
+impl NcData {
+    /// Creates an NcData from the `t_index`-th slice of a time-sliced NetCDF file, using the
+    /// default [`NcSchema`]. See [`TimeSlices`].
+    pub fn open_slice(path: PathBuf, t_index: usize) -> Result<Self> {
+        TimeSlices::open(path)?.get(t_index)
+    }
+
+    /// Creates an NcData from the slice of a time-sliced NetCDF file closest to `time`, using
+    /// the default [`NcSchema`]. See [`TimeSlices`].
+    pub fn open_nearest_time(path: PathBuf, time: f64) -> Result<Self> {
+        TimeSlices::open(path)?.get_nearest(time)
+    }
+}
+
+impl std::fmt::Debug for TimeSlices {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Safe unwrap(); extracted variables are never empty.
+        write!(
+            f,
+            "TimeSlices: {{ path = {:?}, times = [{:.7}, ..., {:.7}], len = {} }}",
+            self.path,
+            self.times.first().unwrap(),
+            self.times.last().unwrap(),
+            self.len(),
+        )
+    }
+}
@@ -0,0 +1,201 @@
+//! Cubic Hermite interpolation of 1D quantities, and the linear systems of cubic splines.
+
+use crate::Interpolation;
+use crate::accel::hunt;
+
+/// Piecewise cubic Hermite interpolation through (x, y) knots with strictly increasing x.
+///
+/// Each piece matches the values on its knots, and the slopes an [`Interpolation`] scheme
+/// assigns to them. Outside the knots, the first or last piece is extended.
+#[derive(Clone)]
+pub(crate) struct Hermite {
+    x: Vec<f64>,
+    y: Vec<f64>,
+    /// Slopes on the (left, right) knots of each piece.
+    slopes: Vec<[f64; 2]>,
+}
+
+impl Hermite {
+    /// Creates the interpolation through the knots. `x` and `y` must have the same, non-zero
+    /// length.
+    pub(crate) fn new<I>(x: &[f64], y: &[f64], scheme: &I) -> Self
+    where
+        I: Interpolation + ?Sized,
+    {
+        Self {
+            x: x.to_vec(),
+            y: y.to_vec(),
+            slopes: scheme.slopes(x, y, None),
+        }
+    }
+
+    /// Returns the span (first, last) of the knots.
+    pub(crate) fn span(&self) -> (f64, f64) {
+        // Safe unwrap()s; there is at least 1 knot.
+        (*self.x.first().unwrap(), *self.x.last().unwrap())
+    }
+
+    /// Returns the interpolation's value and first derivative at `x`, searching for its piece
+    /// from the piece `cell`, which is updated.
+    pub(crate) fn eval(&self, x: f64, cell: &mut usize) -> (f64, f64) {
+        if self.x.len() == 1 {
+            return (self.y[0], 0.0);
+        }
+
+        *cell = hunt(&self.x, x, *cell);
+        let k = *cell;
+        let h = self.x[k + 1] - self.x[k];
+        let (basis, slopes) = hermite((x - self.x[k]) / h);
+        let terms = [
+            self.y[k],
+            self.y[k + 1],
+            h * self.slopes[k][0],
+            h * self.slopes[k][1],
+        ];
+        terms.iter().zip(basis.iter().zip(slopes)).fold(
+            (0.0, 0.0),
+            |(value, derivative), (term, (basis, slope))| {
+                (value + term * basis, derivative + term * slope / h)
+            },
+        )
+    }
+}
+
+/// Returns the cubic Hermite basis functions at `t` in \[0, 1\], for the values on 0 and 1 and
+/// the slopes on 0 and 1, and their derivatives.
+pub(crate) fn hermite(t: f64) -> ([f64; 4], [f64; 4]) {
+    let (t2, t3) = (t * t, t * t * t);
+    let basis = [
+        2.0 * t3 - 3.0 * t2 + 1.0,
+        -2.0 * t3 + 3.0 * t2,
+        t3 - 2.0 * t2 + t,
+        t3 - t2,
+    ];
+    let slopes = [
+        6.0 * t2 - 6.0 * t,
+        -6.0 * t2 + 6.0 * t,
+        3.0 * t2 - 4.0 * t + 1.0,
+        3.0 * t2 - 2.0 * t,
+    ];
+    (basis, slopes)
+}
+
+/// Solves a tridiagonal system with the Thomas algorithm. `lower[0]` and `upper[n - 1]` are
+/// ignored.
+pub(crate) fn solve_tridiagonal(
+    lower: &[f64],
+    diag: &[f64],
+    upper: &[f64],
+    rhs: &[f64],
+) -> Vec<f64> {
+    let n = diag.len();
+    // Forward sweep's modified coefficients.
+    let mut c = vec![0.0; n];
+    let mut d = vec![0.0; n];
+    for k in 0..n {
+        let (l, c_prev, d_prev) = match k {
+            0 => (0.0, 0.0, 0.0),
+            _ => (lower[k], c[k - 1], d[k - 1]),
+        };
+        let pivot = diag[k] - l * c_prev;
+        c[k] = upper[k] / pivot;
+        d[k] = (rhs[k] - l * d_prev) / pivot;
+    }
+    let mut x = d;
+    for k in (0..n - 1).rev() {
+        x[k] -= c[k] * x[k + 1];
+    }
+    x
+}
+
+/// Solves a cyclic tridiagonal system, whose corners are `lower[0]` and `upper[n - 1]`, with the
+/// Sherman-Morrison formula. Needs at least 3 unknowns.
+pub(crate) fn solve_cyclic(lower: &[f64], diag: &[f64], upper: &[f64], rhs: &[f64]) -> Vec<f64> {
+    let n = diag.len();
+    let (alpha, beta) = (upper[n - 1], lower[0]);
+    let gamma = -diag[0];
+
+    let mut modified = diag.to_vec();
+    modified[0] -= gamma;
+    modified[n - 1] -= alpha * beta / gamma;
+    let mut x = solve_tridiagonal(lower, &modified, upper, rhs);
+
+    let mut u = vec![0.0; n];
+    u[0] = gamma;
+    u[n - 1] = alpha;
+    let z = solve_tridiagonal(lower, &modified, upper, &u);
+
+    let factor = (x[0] + beta * x[n - 1] / gamma) / (1.0 + z[0] + beta * z[n - 1] / gamma);
+    x.iter_mut().zip(z).for_each(|(x, z)| *x -= factor * z);
+    x
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Cubic;
+    use ndarray::{Array1, s};
+    use std::f64::consts::TAU;
+
+    #[test]
+    fn test_cubic_spline() {
+        // Lines are reproduced exactly, inside and outside the knots.
+        let x = Array1::from_vec(vec![0.0, 0.5, 0.7, 1.5, 2.0]);
+        let y = x.mapv(|x| 2.0 * x + 1.0);
+        let line = Hermite::new(x.as_slice().unwrap(), y.as_slice().unwrap(), &Cubic);
+        for x in [-1.0, 0.0, 0.6, 2.0, 3.0] {
+            let (value, derivative) = line.eval(x, &mut 0);
+            assert!((value - (2.0 * x + 1.0)).abs() < 1e-12);
+            assert!((derivative - 2.0).abs() < 1e-12);
+        }
+
+        // Knots are interpolated, and the derivative converges away from the natural ends.
+        let x = Array1::linspace(0.0, std::f64::consts::PI, 41);
+        let y = x.mapv(f64::sin);
+        let sine = Hermite::new(x.as_slice().unwrap(), y.as_slice().unwrap(), &Cubic);
+        for (&x, value) in x.iter().zip(x.mapv(f64::sin)) {
+            assert!((sine.eval(x, &mut 0).0 - value).abs() < 1e-14);
+        }
+        let (value, derivative) = sine.eval(1.0, &mut 0);
+        assert!((value - 1.0f64.sin()).abs() < 1e-5);
+        assert!((derivative - 1.0f64.cos()).abs() < 1e-4);
+
+        let constant = Hermite::new(&[0.0], &[0.0], &Cubic);
+        assert_eq!(constant.eval(1.0, &mut 0), (0.0, 0.0));
+
+        // The first derivative is continuous on the knots.
+        for k in 1..x.len() - 1 {
+            assert!((sine.slopes[k - 1][1] - sine.slopes[k][0]).abs() < 1e-12);
+        }
+    }
+
+    #[test]
+    fn test_periodic_slopes() {
+        let slopes = |x: &Array1<f64>, y: Array1<f64>| {
+            let (x, y) = (x.as_slice().unwrap(), y.as_slice().unwrap());
+            let slopes = Cubic.slopes(x, y, Some(TAU));
+            slopes.into_iter().map(|[left, _]| left).collect::<Vec<_>>()
+        };
+
+        // Non-uniform knots of one period.
+        let x = Array1::from_vec(vec![0.1, 0.9, 2.0, 2.7, 3.5, 4.6, 5.2, 5.9]);
+        let f = |x: f64| 1.0 + 0.3 * x.cos() + 0.2 * (2.0 * x).sin();
+        let df = |x: f64| -0.3 * x.sin() + 0.4 * (2.0 * x).cos();
+        for (&x, slope) in x.iter().zip(slopes(&x, x.mapv(f))) {
+            assert!((slope - df(x)).abs() < 0.1);
+        }
+
+        // On uniform knots, the slopes converge much faster.
+        let x = Array1::linspace(0.0, TAU, 65).slice(s![..64]).to_owned();
+        for (&x, slope) in x.iter().zip(slopes(&x, x.mapv(f64::sin))) {
+            assert!((slope - x.cos()).abs() < 1e-5);
+        }
+
+        let two = x.slice(s![..2]).to_owned();
+        assert!(
+            slopes(&two, two.mapv(f))
+                .iter()
+                .all(|slope| slope.is_finite())
+        );
+    }
+}
@@ -0,0 +1,67 @@
+//! Radial subsetting and θ decimation of an `NcData`.
+
+use crate::resample::Weights;
+use crate::{Coords, NcData, NcError, Result};
+
+/// Weights selecting a single grid point.
+fn select(index: usize) -> Weights {
+    [(index, 1.0), (index, 0.0)]
+}
+
+impl NcData {
+    /// Creates a new `NcData` restricted to the surfaces with ψ in \[`psi_a`, `psi_b`\].
+    ///
+    /// No values are interpolated: the new grid is the subset of the current one within the
+    /// range, which must contain at least 2 surfaces. The new `psi_wall` is the outermost kept
+    /// surface, and the extrapolated axis value is kept only if the range includes it.
+    ///
+    /// ```
+    /// # use tokamak_netcdf::{CircularTokamak, NcError};
+    /// # fn main() -> Result<(), NcError> {
+    /// let nc_data = CircularTokamak::default().to_nc_data()?;
+    /// let core = nc_data.subset_psi(0.0, 0.5 * nc_data.scalars.psi_wall)?;
+    /// assert!(core.validate().is_valid());
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn subset_psi(&self, psi_a: f64, psi_b: f64) -> Result<NcData> {
+        let indices: Vec<usize> = (0..self.coords.psi_len)
+            .filter(|&k| (psi_a..=psi_b).contains(&self.coords.psi[k]))
+            .collect();
+        if indices.len() < 2 {
+            return Err(NcError::InvalidGrid(
+                format!("[{psi_a}, {psi_b}] contains fewer than 2 surfaces").into(),
+            ));
+        }
+
+        let psi = indices.iter().map(|&k| self.coords.psi[k]).collect();
+        let extrapolated_axis = self.coords.extrapolated_axis && indices[0] == 0;
+        let psi_weights: Vec<Weights> = indices.into_iter().map(select).collect();
+        let theta_weights: Vec<Weights> = (0..self.coords.theta_len).map(select).collect();
+
+        let coords = Coords::new(psi, self.coords.theta.clone(), extrapolated_axis);
+        self.regrid(coords, &psi_weights, &theta_weights)
+    }
+
+    /// Creates a new `NcData` keeping every `step`-th θ value, starting from the first. At least
+    /// 2 θ values must be kept.
+    pub fn decimate_theta(&self, step: usize) -> Result<NcData> {
+        if step == 0 || self.coords.theta_len.div_ceil(step) < 2 {
+            return Err(NcError::InvalidGrid(
+                format!("decimating by {step} keeps fewer than 2 θ values").into(),
+            ));
+        }
+
+        let indices: Vec<usize> = (0..self.coords.theta_len).step_by(step).collect();
+        let theta = indices.iter().map(|&j| self.coords.theta[j]).collect();
+        let psi_weights: Vec<Weights> = (0..self.coords.psi_len).map(select).collect();
+        let theta_weights: Vec<Weights> = indices.into_iter().map(select).collect();
+
+        let coords = Coords::new(
+            self.coords.psi.clone(),
+            theta,
+            self.coords.extrapolated_axis,
+        );
+        self.regrid(coords, &psi_weights, &theta_weights)
+    }
+}
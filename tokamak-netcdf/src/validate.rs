@@ -0,0 +1,206 @@
+//! Consistency checks of an `NcData`.
+
+use std::f64::consts::TAU;
+
+use ndarray::{ArrayBase, ArrayView1, Data, Dimension};
+
+use crate::{NcData, NcError, Result};
+
+/// Problems found by [`NcData::validate`].
+pub struct ValidationReport {
+    /// All problems found, in the order they were checked.
+    pub errors: Vec<NcError>,
+}
+
+impl ValidationReport {
+    /// Returns `true` if no problems were found.
+    pub fn is_valid(&self) -> bool {
+        self.errors.is_empty()
+    }
+}
+
+impl std::fmt::Display for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.errors.len() {
+            0 => write!(f, "No problems found."),
+            n => {
+                writeln!(f, "{} problem(s) found:", n)?;
+                for err in &self.errors {
+                    writeln!(f, "    {}", err)?;
+                }
+                Ok(())
+            }
+        }
+    }
+}
+
+impl std::fmt::Debug for ValidationReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self)
+    }
+}
+
+impl NcData {
+    /// Checks the equilibrium for problems that opening the file does not catch, and reports all
+    /// of them at once:
+    ///
+    /// + ψ or θ not strictly increasing ([`NcError::NonMonotonic`]),
+    /// + θ not covering a full period ([`NcError::IncompletePeriod`]),
+    /// + NaN or infinite values ([`NcError::NonFinite`]),
+    /// + non-positive magnetic field strength ([`NcError::NonPositive`]),
+    /// + components not sharing the (ψ, θ) grid of the coordinates ([`NcError::ShapeMismatch`]).
+    pub fn validate(&self) -> ValidationReport {
+        let schema = &self.schema;
+        let mut errors = Vec::new();
+
+        for (name, coord) in [
+            (&schema.psi, &self.coords.psi),
+            (&schema.theta, &self.coords.theta),
+        ] {
+            if coord.windows(2).into_iter().any(|w| w[1] <= w[0]) {
+                errors.push(NcError::NonMonotonic(name.clone()));
+            }
+        }
+
+        if let Some(span) = incomplete_period(self.coords.theta.as_slice().unwrap_or(&[])) {
+            errors.push(NcError::IncompletePeriod {
+                name: schema.theta.clone(),
+                span,
+            });
+        }
+
+        let mut check_finite = |name: &str, count: usize| {
+            if count > 0 {
+                errors.push(NcError::NonFinite {
+                    name: name.into(),
+                    count,
+                });
+            }
+        };
+        check_finite(&schema.psi, non_finite(&self.coords.psi));
+        check_finite(&schema.theta, non_finite(&self.coords.theta));
+        check_finite(&schema.i, non_finite(&self.currents.i));
+        check_finite(&schema.g, non_finite(&self.currents.g));
+        check_finite(&schema.b_field, non_finite(&self.bfield.b));
+        if let Some(profiles) = &self.profiles {
+            if let Some(q) = &profiles.q {
+                check_finite(&schema.q, non_finite(q));
+            }
+            if let Some(psip) = &profiles.psip {
+                check_finite(&schema.psip, non_finite(psip));
+            }
+        }
+        if let Some(geometry) = &self.geometry {
+            check_finite(&schema.r, non_finite(&geometry.r));
+            check_finite(&schema.z, non_finite(&geometry.z));
+        }
+
+        let non_positive = self.bfield.b.iter().filter(|&&b| b <= 0.0).count();
+        if non_positive > 0 {
+            errors.push(NcError::NonPositive {
+                name: schema.b_field.clone(),
+                count: non_positive,
+            });
+        }
+
+        errors.extend(self.shape_errors());
+        ValidationReport { errors }
+    }
+
+    /// Checks that all components share the (ψ, θ) grid of the coordinates.
+    pub(crate) fn shape_errors(&self) -> Vec<NcError> {
+        let schema = &self.schema;
+        let grid_1d = [self.coords.psi_len];
+        let grid_2d = [self.coords.psi_len, self.coords.theta_len];
+
+        let mut arrays: Vec<(&str, &[usize], &[usize])> = vec![
+            (&schema.i, self.currents.i.shape(), &grid_1d),
+            (&schema.g, self.currents.g.shape(), &grid_1d),
+            (&schema.b_field, self.bfield.b.shape(), &grid_2d),
+        ];
+        if let Some(profiles) = &self.profiles {
+            if let Some(q) = &profiles.q {
+                arrays.push((&schema.q, q.shape(), &grid_1d));
+            }
+            if let Some(psip) = &profiles.psip {
+                arrays.push((&schema.psip, psip.shape(), &grid_1d));
+            }
+        }
+        if let Some(geometry) = &self.geometry {
+            arrays.push((&schema.r, geometry.r.shape(), &grid_2d));
+            arrays.push((&schema.z, geometry.z.shape(), &grid_2d));
+        }
+
+        arrays
+            .into_iter()
+            .filter_map(|(name, found, expected)| check_shape(name, expected, found).err())
+            .collect()
+    }
+}
+
+/// Checks that the array `name` has the `expected` shape, or returns a
+/// [`NcError::ShapeMismatch`].
+pub(crate) fn check_shape(name: &str, expected: &[usize], found: &[usize]) -> Result<()> {
+    match expected == found {
+        true => Ok(()),
+        false => Err(NcError::ShapeMismatch {
+            name: name.into(),
+            expected: expected.into(),
+            found: found.into(),
+        }),
+    }
+}
+
+/// Counts the NaN and infinite values of an array.
+fn non_finite<S, D>(arr: &ArrayBase<S, D>) -> usize
+where
+    S: Data<Elem = f64>,
+    D: Dimension,
+{
+    arr.iter().filter(|x| !x.is_finite()).count()
+}
+
+/// Tolerance of θ period comparisons, relative for grids written in single precision.
+const PERIOD_TOL: f64 = 1e-6 * TAU;
+
+/// Returns the θ grid's span if it does not cover a full period. The grid may or may not include
+/// the 2π endpoint, so the gap left to close the period must be between 0 and the largest step.
+pub(crate) fn incomplete_period(theta: &[f64]) -> Option<f64> {
+    let (first, last) = (*theta.first()?, *theta.last()?);
+    let span = last - first;
+    let max_step = theta.windows(2).map(|w| w[1] - w[0]).fold(0.0, f64::max);
+
+    let gap = TAU - span;
+    match (-PERIOD_TOL..=max_step + PERIOD_TOL).contains(&gap) {
+        true => None,
+        false => Some(span),
+    }
+}
+
+/// Returns the number of distinct θ values within a period, which excludes a last value one
+/// period after the first.
+pub(crate) fn period_len(theta: ArrayView1<f64>) -> usize {
+    let n = theta.len();
+    match n > 1 && theta[n - 1] - theta[0] >= TAU - PERIOD_TOL {
+        true => n - 1,
+        false => n,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_incomplete_period() {
+        let with_endpoint: Vec<f64> = (0..=8).map(|i| i as f64 * TAU / 8.0).collect();
+        let without_endpoint: Vec<f64> = (0..8).map(|i| i as f64 * TAU / 8.0).collect();
+        let half: Vec<f64> = (0..=8).map(|i| i as f64 * TAU / 16.0).collect();
+        let too_long: Vec<f64> = (0..=8).map(|i| i as f64 * TAU / 7.0).collect();
+
+        assert!(incomplete_period(&with_endpoint).is_none());
+        assert!(incomplete_period(&without_endpoint).is_none());
+        assert!(incomplete_period(&half).is_some());
+        assert!(incomplete_period(&too_long).is_some());
+    }
+}
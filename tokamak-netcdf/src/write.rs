@@ -0,0 +1,253 @@
+//! Writes an `NcData` to a NetCDF file.
+
+use std::path::Path;
+
+use ndarray::{ArrayBase, Data, Dimension, s};
+
+use crate::extract::group_path;
+use crate::{NcData, NcError, Result};
+
+/// Units written as the `units` attribute of each quantity. The crate does not know the units of
+/// the R(ψ, θ) and Z(ψ, θ) maps, so they are written without one.
+mod units {
+    pub(super) const NORMALISED: &str = "normalised";
+    pub(super) const DIMENSIONLESS: &str = "1";
+    pub(super) const RADIANS: &str = "rad";
+    pub(super) const TESLA: &str = "T";
+    pub(super) const METERS: &str = "m";
+}
+
+impl NcData {
+    /// Writes the equilibrium to a NetCDF file, in the layout [`NcData::open_with`] reads with
+    /// the same [`NcSchema`](crate::NcSchema).
+    ///
+    /// Values extrapolated to the magnetic axis are not written, so opening the written file
+    /// with `self.schema` recreates the same `NcData`.
+    pub fn write(&self, path: &Path) -> Result<()> {
+        let schema = &self.schema;
+        let skip = usize::from(self.coords.extrapolated_axis);
+
+        let mut f = netcdf::create(path).map_err(library_error("Error creating NetCDF file"))?;
+        let names = [
+            &schema.psi,
+            &schema.theta,
+            &schema.b_field,
+            &schema.g,
+            &schema.i,
+            &schema.baxis,
+            &schema.raxis,
+            &schema.q,
+            &schema.psip,
+            &schema.r,
+            &schema.z,
+        ];
+        let path = |name: &str| group_path(&schema.group, name);
+        for name in names {
+            add_parent_group(&mut f, &path(name))?;
+        }
+
+        // Evidently `psi` and `boozer_theta` appear both as coords and variables. Dimensions are
+        // referred to by their last path component and looked up from each variable's group
+        // upwards, so they are added to the innermost group containing all variables.
+        let dims_group = names.iter().map(|name| parent(&path(name))).fold(
+            parent(&path(&schema.psi)),
+            |common, parent| {
+                let shared: Vec<&str> = common
+                    .split('/')
+                    .zip(parent.split('/'))
+                    .take_while(|(a, b)| a == b)
+                    .map(|(a, _)| a)
+                    .collect();
+                shared.join("/")
+            },
+        );
+        let psi_dim = [stem(&schema.psi)];
+        let grid_dims = [stem(&schema.psi), stem(&schema.theta)];
+        f.add_dimension(
+            &group_path(&dims_group, psi_dim[0]),
+            self.coords.psi_len - skip,
+        )
+        .map_err(library_error("Error adding ψ dimension"))?;
+        f.add_dimension(
+            &group_path(&dims_group, grid_dims[1]),
+            self.coords.theta_len,
+        )
+        .map_err(library_error("Error adding θ dimension"))?;
+
+        write_scalar(
+            &mut f,
+            &path(&schema.baxis),
+            self.scalars.baxis,
+            units::TESLA,
+        )?;
+        write_scalar(
+            &mut f,
+            &path(&schema.raxis),
+            self.scalars.raxis,
+            units::METERS,
+        )?;
+
+        let psi = self.coords.psi.slice(s![skip..]);
+        write_array(
+            &mut f,
+            &path(&schema.psi),
+            &psi_dim,
+            &psi,
+            Some(units::NORMALISED),
+        )?;
+        write_array(
+            &mut f,
+            &path(&schema.theta),
+            &[stem(&schema.theta)],
+            &self.coords.theta,
+            Some(units::RADIANS),
+        )?;
+
+        let i = self.currents.i.slice(s![skip..]);
+        let g = self.currents.g.slice(s![skip..]);
+        write_array(
+            &mut f,
+            &path(&schema.i),
+            &psi_dim,
+            &i,
+            Some(units::NORMALISED),
+        )?;
+        write_array(
+            &mut f,
+            &path(&schema.g),
+            &psi_dim,
+            &g,
+            Some(units::NORMALISED),
+        )?;
+
+        let b = self.bfield.b.slice(s![skip.., ..]);
+        write_array(
+            &mut f,
+            &path(&schema.b_field),
+            &grid_dims,
+            &b,
+            Some(units::NORMALISED),
+        )?;
+
+        if let Some(profiles) = &self.profiles {
+            if let Some(q) = &profiles.q {
+                let q = q.slice(s![skip..]);
+                write_array(
+                    &mut f,
+                    &path(&schema.q),
+                    &psi_dim,
+                    &q,
+                    Some(units::DIMENSIONLESS),
+                )?;
+            }
+            if let Some(psip) = &profiles.psip {
+                let psip = psip.slice(s![skip..]);
+                write_array(
+                    &mut f,
+                    &path(&schema.psip),
+                    &psi_dim,
+                    &psip,
+                    Some(units::NORMALISED),
+                )?;
+            }
+        }
+        if let Some(geometry) = &self.geometry {
+            let r = geometry.r.slice(s![skip.., ..]);
+            let z = geometry.z.slice(s![skip.., ..]);
+            write_array(&mut f, &path(&schema.r), &grid_dims, &r, None)?;
+            write_array(&mut f, &path(&schema.z), &grid_dims, &z, None)?;
+        }
+
+        self.write_metadata(&mut f)?;
+
+        f.close()
+            .map_err(library_error("Error closing NetCDF file"))
+    }
+}
+
+impl NcData {
+    /// Copies the global attributes, with their types, and the variables' `long_name` attributes
+    /// from `metadata`. `units` are not copied, since they are
+    /// determined by the written quantities.
+    fn write_metadata(&self, f: &mut netcdf::FileMut) -> Result<()> {
+        for (name, value) in &self.metadata.attributes {
+            f.add_attribute(name, netcdf::AttributeValue::from(value.clone()))
+                .map_err(library_error("Error writing attribute"))?;
+        }
+        for (name, meta) in &self.metadata.variables {
+            let path = group_path(&self.schema.group, name);
+            if let (Some(long_name), Some(mut var)) = (&meta.long_name, f.variable_mut(&path)) {
+                var.put_attribute("long_name", &**long_name)
+                    .map_err(library_error("Error writing attribute"))?;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Maps a netcdf library error to an [`NcError::LibraryError`] with the given reason.
+fn library_error(reason: &str) -> impl FnOnce(netcdf::Error) -> NcError + '_ {
+    move |source| NcError::LibraryError {
+        source,
+        reason: reason.into(),
+    }
+}
+
+/// Returns the last component of a variable's path.
+fn stem(name: &str) -> &str {
+    name.rsplit('/').next().unwrap_or(name)
+}
+
+/// Returns the path of the group containing a variable.
+fn parent(path: &str) -> String {
+    path.rsplit_once('/')
+        .map_or(String::new(), |(parent, _)| parent.into())
+}
+
+/// Creates the group containing the variable at `path`, along with its parents, if it does not
+/// already exist.
+fn add_parent_group(f: &mut netcdf::FileMut, path: &str) -> Result<()> {
+    let parent = parent(path);
+    if !parent.is_empty() && !matches!(f.group(&parent), Ok(Some(_))) {
+        f.add_group(&parent)
+            .map_err(library_error("Error adding group"))?;
+    }
+    Ok(())
+}
+
+/// Adds a scalar (0D) variable with a `units` attribute to the NetCDF file.
+fn write_scalar(f: &mut netcdf::FileMut, name: &str, value: f64, units: &str) -> Result<()> {
+    let mut var = f
+        .add_variable::<f64>(name, &[])
+        .map_err(library_error("Error adding variable"))?;
+    var.put_value(value, ..)
+        .map_err(library_error("Error writing variable"))?;
+    var.put_attribute("units", units)
+        .map_err(library_error("Error writing attribute"))?;
+    Ok(())
+}
+
+/// Adds an N-dimensional variable, with an optional `units` attribute, to the NetCDF file.
+fn write_array<S, D>(
+    f: &mut netcdf::FileMut,
+    name: &str,
+    dims: &[&str],
+    arr: &ArrayBase<S, D>,
+    units: Option<&str>,
+) -> Result<()>
+where
+    S: Data<Elem = f64>,
+    D: Dimension,
+{
+    let mut var = f
+        .add_variable::<f64>(name, dims)
+        .map_err(library_error("Error adding variable"))?;
+    let extents: Vec<std::ops::RangeFull> = vec![..; dims.len()];
+    var.put(arr.as_standard_layout().view(), extents.as_slice())
+        .map_err(library_error("Error writing variable"))?;
+    if let Some(units) = units {
+        var.put_attribute("units", units)
+            .map_err(library_error("Error writing attribute"))?;
+    }
+    Ok(())
+}
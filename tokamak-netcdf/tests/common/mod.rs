@@ -1,37 +1,525 @@
+// Without `netcdf`, the tests reading NETCDF4 fixtures are disabled, and so are their helpers.
+#![cfg_attr(not(feature = "netcdf"), allow(dead_code))]
+
 use ndarray::{Array, Ix2};
-use std::path::PathBuf;
+use std::f64::consts::PI;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use tokamak_netcdf::NcSchema;
+
+/// Returns a path in the temporary directory that no other test uses, even across test
+/// processes.
+pub(crate) fn unique_temp_path(stem: &str) -> PathBuf {
+    static COUNTER: AtomicUsize = AtomicUsize::new(0);
+    let count = COUNTER.fetch_add(1, Ordering::Relaxed);
+    let name = format!("{stem}_{}_{count}.nc", std::process::id());
+    std::env::temp_dir().join(name)
+}
 
 /// Creates a phony NetCDF file simulating the actual equilibrium.
 pub(crate) fn phony_netcdf_path() -> Result<PathBuf, netcdf::Error> {
-    let path = std::env::temp_dir().join("phony.nc");
+    let path = unique_temp_path("phony");
+    phony_netcdf_path_with(path.to_str().unwrap(), &NcSchema::default())
+}
+
+/// Returns the contents of a phony NetCDF file simulating the actual equilibrium, without
+/// leaving a file behind.
+pub(crate) fn phony_netcdf_bytes() -> Result<Vec<u8>, netcdf::Error> {
+    let path = phony_netcdf_path()?;
+    let bytes = std::fs::read(&path).unwrap();
+    std::fs::remove_file(path).unwrap();
+    Ok(bytes)
+}
+
+/// Creates a phony NetCDF file simulating the actual equilibrium, with its variables named
+/// according to `schema`. A relative `filename` is placed in the temporary directory.
+pub(crate) fn phony_netcdf_path_with(
+    filename: &str,
+    schema: &NcSchema,
+) -> Result<PathBuf, netcdf::Error> {
+    let path = std::env::temp_dir().join(filename);
     let path_str = path.to_str().unwrap();
 
     let mut f = netcdf::create(path_str)?;
 
     // Scalars
-    f.add_variable::<f64>("Baxis", &[])?
+    f.add_variable::<f64>(&schema.baxis, &[])?
         .put_values(&[1.0], ..)?;
-    f.add_variable::<f64>("raxis", &[])?
+    f.add_variable::<f64>(&schema.raxis, &[])?
         .put_values(&[1.65], ..)?;
 
     // 1D variables
     // Evidently `psi` and `boozer_theta` appear both as coords and variables.
     let shape = (2, 3);
-    f.add_dimension("psi", shape.0)?;
-    f.add_dimension("boozer_theta", shape.1)?;
+    f.add_dimension(&schema.psi, shape.0)?;
+    f.add_dimension(&schema.theta, shape.1)?;
 
-    f.add_variable::<f64>("psi", &["psi"])?;
-    f.add_variable::<f64>("boozer_theta", &["boozer_theta"])?;
-    f.add_variable::<f64>("I_norm", &["psi"])?
+    f.add_variable::<f64>(&schema.psi, &[&schema.psi])?
+        .put_values(&[0.1, 0.2], ..)?;
+    f.add_variable::<f64>(&schema.theta, &[&schema.theta])?
+        .put_values(&[0.0, 2.0 * PI / 3.0, 4.0 * PI / 3.0], ..)?;
+    f.add_variable::<f64>(&schema.i, &[&schema.psi])?
         .put_values(&[0.0, 0.1], ..)?;
-    f.add_variable::<f64>("g_norm", &["psi"])?
+    f.add_variable::<f64>(&schema.g, &[&schema.psi])?
         .put_values(&[0.2, 0.1], ..)?;
 
     // 2D variable
     let b_values =
         Array::<f64, Ix2>::from_shape_vec(shape, vec![0.1, 0.2, 0.3, 0.4, 0.5, 0.6]).unwrap();
-    f.add_variable::<f64>("b_field_norm", &["psi", "boozer_theta"])?
+    f.add_variable::<f64>(&schema.b_field, &[&schema.psi, &schema.theta])?
         .put(b_values.view(), (.., ..))?;
 
     f.path()
 }
+
+/// Creates a NetCDF file holding two equilibria like the phony one, in the `shot1/equilibrium`
+/// and `shot2/equilibrium` groups, with each shot's magnetic field scaled by its number. Each
+/// shot group has a `shot` attribute, and the file has a shared `/profiles/q`.
+pub(crate) fn grouped_netcdf_path() -> Result<PathBuf, netcdf::Error> {
+    let path = unique_temp_path("grouped");
+    let schema = NcSchema::default();
+    let mut f = netcdf::create(&path)?;
+    f.add_attribute("code", "phony")?;
+
+    for shot in [1, 2] {
+        f.add_group(&format!("shot{shot}"))?
+            .add_attribute("shot", shot)?;
+        let mut g = f.add_group(&format!("shot{shot}/equilibrium"))?;
+        g.add_variable::<f64>(&schema.baxis, &[])?
+            .put_values(&[1.0], ..)?;
+        g.add_variable::<f64>(&schema.raxis, &[])?
+            .put_values(&[1.65], ..)?;
+
+        g.add_dimension(&schema.psi, 2)?;
+        g.add_dimension(&schema.theta, 3)?;
+        g.add_variable::<f64>(&schema.psi, &[&schema.psi])?
+            .put_values(&[0.1, 0.2], ..)?;
+        g.add_variable::<f64>(&schema.theta, &[&schema.theta])?
+            .put_values(&[0.0, 2.0 * PI / 3.0, 4.0 * PI / 3.0], ..)?;
+        g.add_variable::<f64>(&schema.i, &[&schema.psi])?
+            .put_values(&[0.0, 0.1], ..)?;
+        g.add_variable::<f64>(&schema.g, &[&schema.psi])?
+            .put_values(&[0.2, 0.1], ..)?;
+
+        let b_values: Vec<f64> = [0.1, 0.2, 0.3, 0.4, 0.5, 0.6]
+            .iter()
+            .map(|b| b * shot as f64)
+            .collect();
+        g.add_variable::<f64>(&schema.b_field, &[&schema.psi, &schema.theta])?
+            .put_values(&b_values, (.., ..))?;
+    }
+
+    let mut profiles = f.add_group("profiles")?;
+    profiles.add_dimension("n", 2)?;
+    profiles
+        .add_variable::<f64>(&schema.q, &["n"])?
+        .put_values(&[1.1, 2.5], ..)?;
+
+    Ok(path)
+}
+
+/// Adds the safety factor and the R(ψ, θ), Z(ψ, θ) maps to an existing phony NetCDF file. The
+/// poloidal flux is deliberately left out.
+pub(crate) fn add_optional_variables(path: &Path, schema: &NcSchema) -> Result<(), netcdf::Error> {
+    let mut f = netcdf::append(path)?;
+
+    f.add_variable::<f64>(&schema.q, &[&schema.psi])?
+        .put_values(&[1.1, 2.5], ..)?;
+
+    let r_values =
+        Array::<f64, Ix2>::from_shape_vec((2, 3), vec![1.1, 1.0, 0.9, 1.2, 1.0, 0.8]).unwrap();
+    let z_values =
+        Array::<f64, Ix2>::from_shape_vec((2, 3), vec![0.0, 0.1, -0.1, 0.0, 0.2, -0.2]).unwrap();
+    f.add_variable::<f64>(&schema.r, &[&schema.psi, &schema.theta])?
+        .put(r_values.view(), (.., ..))?;
+    f.add_variable::<f64>(&schema.z, &[&schema.psi, &schema.theta])?
+        .put(z_values.view(), (.., ..))?;
+
+    Ok(())
+}
+
+/// Creates a phony NetCDF file whose magnetic field is stored as packed short integers, named
+/// "b_packed". If `fill` is true, its last cell is set to the `_FillValue`.
+pub(crate) fn packed_netcdf_path(fill: bool) -> Result<PathBuf, netcdf::Error> {
+    let path = phony_netcdf_path()?;
+    let mut f = netcdf::append(&path)?;
+
+    // B = 0.1 * packed + 0.1, matching the phony file's b_field.
+    let mut packed: Vec<i16> = vec![0, 1, 2, 3, 4, 5];
+    if fill {
+        packed[5] = i16::MIN;
+    }
+    let mut var = f.add_variable::<i16>("b_packed", &["psi", "boozer_theta"])?;
+    var.put_attribute("scale_factor", 0.1)?;
+    var.put_attribute("add_offset", 0.1)?;
+    var.put_attribute("_FillValue", i16::MIN)?;
+    var.put_values(&packed, (.., ..))?;
+
+    Ok(path)
+}
+
+/// Creates a phony NetCDF file in one of the NetCDF-3 formats, with metadata attributes and a
+/// magnetic field stored as packed short integers.
+#[cfg(feature = "classic")]
+pub(crate) fn classic_netcdf_path(options: netcdf::Options) -> Result<PathBuf, netcdf::Error> {
+    let schema = NcSchema::default();
+    let path = unique_temp_path("classic");
+    let mut f = netcdf::create_with(&path, options)?;
+    f.add_attribute("shot", 42)?;
+    f.add_attribute("time", 1.5)?;
+    f.add_attribute("code", "phony")?;
+
+    // Classic files leave define mode once, after all of their variables are defined.
+    f.add_dimension(&schema.psi, 2)?;
+    f.add_dimension(&schema.theta, 3)?;
+    f.add_variable::<f64>(&schema.baxis, &[])?;
+    f.add_variable::<f64>(&schema.raxis, &[])?;
+    f.add_variable::<f64>(&schema.psi, &[&schema.psi])?
+        .put_attribute("long_name", "toroidal flux")?;
+    f.add_variable::<f32>(&schema.theta, &[&schema.theta])?;
+    f.add_variable::<f64>(&schema.i, &[&schema.psi])?;
+    f.add_variable::<i32>(&schema.g, &[&schema.psi])?;
+    let mut b = f.add_variable::<i16>(&schema.b_field, &[&schema.psi, &schema.theta])?;
+    b.put_attribute("scale_factor", 0.1f32)?;
+    b.put_attribute("add_offset", 0.1)?;
+    f.enddef()?;
+
+    let mut put = |name: &str, values: &[f64]| f.variable_mut(name).unwrap().put_values(values, ..);
+    put(&schema.baxis, &[1.0])?;
+    put(&schema.raxis, &[1.65])?;
+    put(&schema.psi, &[0.1, 0.2])?;
+    put(&schema.theta, &[0.0, 2.0 * PI / 3.0, 4.0 * PI / 3.0])?;
+    put(&schema.i, &[0.0, 0.1])?;
+    put(&schema.g, &[2.0, 1.0])?;
+    f.variable_mut(&schema.b_field)
+        .unwrap()
+        .put_values(&[0i16, 1, 2, 3, 4, 5], (.., ..))?;
+
+    Ok(path)
+}
+
+/// Creates an IMAS netCDF file whose equilibrium IDS holds 2 time slices of the circular
+/// equilibrium of [`circular_gfile_path`], with B0 = 2 T and 3 T, R0 = 1.65 m and q = 2. The flux
+/// follows COCOS 11, or COCOS 17 if `dd_version` is 4 or later. The 1D profiles are given on a
+/// non-uniform flux grid, and the rectangular grid is the second `profiles_2d` entry.
+pub(crate) fn imas_netcdf_path(dd_version: &str) -> Result<PathBuf, netcdf::Error> {
+    imas_path(dd_version, false)
+}
+
+/// Creates the equilibrium of [`imas_netcdf_path`] in the layout of the Access Layer's HDF5
+/// backend, with padded arrays and the Data Dictionary version in `ids_properties`.
+pub(crate) fn imas_hdf5_path(dd_version: &str) -> Result<PathBuf, netcdf::Error> {
+    imas_path(dd_version, true)
+}
+
+fn imas_path(dd_version: &str, access_layer: bool) -> Result<PathBuf, netcdf::Error> {
+    let path = unique_temp_path("imas");
+    let (raxis, q, n) = (1.65, 2.0, 65);
+    let (minor, rdim) = (0.5, 1.3);
+    let sign = match dd_version.starts_with('4') {
+        true => -2.0 * PI,
+        false => 2.0 * PI,
+    };
+    let grid: Vec<f64> = (0..n)
+        .map(|i| -0.5 * rdim + rdim * i as f64 / (n - 1) as f64)
+        .collect();
+    // The Access Layer pads arrays with its empty value, and names quantities with `&`.
+    let (pad, empty) = (if access_layer { 3 } else { 0 }, -9.0e40);
+    let name = |path: &str| match access_layer {
+        false => path.to_string(),
+        true => path
+            .replace("time_slice.", "time_slice[]&")
+            .replace("profiles_2d.", "profiles_2d[]&")
+            .replace('.', "&"),
+    };
+
+    let mut f = netcdf::create(&path)?;
+    let mut ids = f.add_group("equilibrium")?;
+    match access_layer {
+        false => {
+            ids.add_attribute("data_dictionary_version", dd_version)?;
+        }
+        true => {
+            ids.add_string_variable("ids_properties&version_put&data_dictionary", &[])?
+                .put_string(dd_version, ..)?;
+        }
+    }
+    ids.add_dimension("time", 2)?;
+    ids.add_dimension("time_slice.profiles_1d.psi:i", 9 + pad)?;
+    ids.add_dimension("time_slice.profiles_2d", 2)?;
+    ids.add_dimension("time_slice.profiles_2d.grid.dim1:i", n + pad)?;
+    ids.add_dimension("time_slice.profiles_2d.grid.dim2:i", n + pad)?;
+    let (p1d, p2d) = ("time_slice.profiles_1d.psi:i", "time_slice.profiles_2d");
+    let (dim1, dim2) = (
+        "time_slice.profiles_2d.grid.dim1:i",
+        "time_slice.profiles_2d.grid.dim2:i",
+    );
+
+    ids.add_variable::<f64>("time", &["time"])?
+        .put_values(&[0.1, 0.2], ..)?;
+    ids.add_variable::<f64>(&name("time_slice.time"), &["time"])?
+        .put_values(&[0.1, 0.2], ..)?;
+    ids.add_variable::<f64>(&name("vacuum_toroidal_field.r0"), &[])?
+        .put_values(&[raxis], ..)?;
+    ids.add_variable::<f64>(&name("vacuum_toroidal_field.b0"), &["time"])?
+        .put_values(&[2.0, 3.0], ..)?;
+    for (quantity, values) in [
+        ("magnetic_axis.r", [raxis; 2]),
+        ("magnetic_axis.z", [0.0; 2]),
+        ("psi_axis", [0.0; 2]),
+        ("ip", [1.0e6; 2]),
+    ] {
+        let path = format!("time_slice.global_quantities.{quantity}");
+        ids.add_variable::<f64>(&name(&path), &["time"])?
+            .put_values(&values, ..)?;
+    }
+
+    // Per radian, the flux is b0 r² / (2 q R0) on a circle of minor radius r.
+    let psi_rz = |b0: f64, r: f64, z: f64| sign * b0 * (r * r + z * z) / (2.0 * q * raxis);
+    let psi_n: Vec<f64> = (0..9).map(|k| (k as f64 / 8.0).powi(2)).collect();
+    let padding = |len: usize| std::iter::repeat_n(empty, len);
+    let (mut psi_bry, mut psi_1d, mut f_1d, mut psi_2d) = (vec![], vec![], vec![], vec![]);
+    for b0 in [2.0, 3.0] {
+        let boundary = psi_rz(b0, minor, 0.0);
+        psi_bry.push(boundary);
+        psi_1d.extend(psi_n.iter().map(|x| x * boundary).chain(padding(pad)));
+        f_1d.extend([b0 * raxis; 9].into_iter().chain(padding(pad)));
+        for _ in 0..2 {
+            for &r in &grid {
+                psi_2d.extend(grid.iter().map(|&z| psi_rz(b0, r, z)).chain(padding(pad)));
+            }
+            psi_2d.extend(padding(pad * (n + pad)));
+        }
+    }
+    let q_1d: Vec<f64> = (0..2)
+        .flat_map(|_| [q; 9].into_iter().chain(padding(pad)))
+        .collect();
+    ids.add_variable::<f64>(
+        &name("time_slice.global_quantities.psi_boundary"),
+        &["time"],
+    )?
+    .put_values(&psi_bry, ..)?;
+    for (path, values) in [
+        ("time_slice.profiles_1d.psi", &psi_1d),
+        ("time_slice.profiles_1d.f", &f_1d),
+        ("time_slice.profiles_1d.q", &q_1d),
+    ] {
+        ids.add_variable::<f64>(&name(path), &["time", p1d])?
+            .put_values(values, (.., ..))?;
+    }
+
+    // The first profiles_2d entry is on an inverse (ψ, θ) grid, which the importer skips.
+    ids.add_variable::<i32>(
+        &name("time_slice.profiles_2d.grid_type.index"),
+        &["time", p2d],
+    )?
+    .put_values(&[2, 1, 2, 1], (.., ..))?;
+    let dims: Vec<f64> = (0..4)
+        .flat_map(|_| grid.iter().map(|x| x + raxis).chain(padding(pad)))
+        .collect();
+    ids.add_variable::<f64>(
+        &name("time_slice.profiles_2d.grid.dim1"),
+        &["time", p2d, dim1],
+    )?
+    .put_values(&dims, (.., .., ..))?;
+    let dims: Vec<f64> = (0..4)
+        .flat_map(|_| grid.iter().copied().chain(padding(pad)))
+        .collect();
+    ids.add_variable::<f64>(
+        &name("time_slice.profiles_2d.grid.dim2"),
+        &["time", p2d, dim2],
+    )?
+    .put_values(&dims, (.., .., ..))?;
+    ids.add_variable::<f64>(
+        &name("time_slice.profiles_2d.psi"),
+        &["time", p2d, dim1, dim2],
+    )?
+    .put_values(&psi_2d, (.., .., .., ..))?;
+
+    if access_layer {
+        ids.add_dimension("one", 1)?;
+        ids.add_dimension("two", 2)?;
+        for path in [
+            "time_slice.profiles_1d.psi",
+            "time_slice.profiles_1d.f",
+            "time_slice.profiles_1d.q",
+        ] {
+            ids.add_variable::<i32>(&format!("{}_SHAPE", name(path)), &["time", "one"])?
+                .put_values(&[9, 9], (.., ..))?;
+        }
+        for (path, shape) in [
+            ("time_slice.profiles_2d.grid.dim1", vec![n as i32; 4]),
+            ("time_slice.profiles_2d.grid.dim2", vec![n as i32; 4]),
+            ("time_slice.profiles_2d.psi", vec![n as i32; 8]),
+        ] {
+            let size = match shape.len() {
+                4 => "one",
+                _ => "two",
+            };
+            ids.add_variable::<i32>(&format!("{}_SHAPE", name(path)), &["time", p2d, size])?
+                .put_values(&shape, (.., .., ..))?;
+        }
+    }
+
+    Ok(path)
+}
+
+/// Creates an EQDSK G-file of a circular, large aspect ratio equilibrium with constant safety
+/// factor `q` and poloidal current F = `baxis` * `raxis`, with minor radius 0.5.
+pub(crate) fn circular_gfile_path(filename: &str, baxis: f64, raxis: f64, q: f64) -> PathBuf {
+    let path = std::env::temp_dir().join(filename);
+    let (nw, nh) = (65, 65);
+    let (minor, rdim, zdim) = (0.5, 1.3, 1.3);
+    let rleft = raxis - 0.5 * rdim;
+    // Bp = r B0 / (q R0) on the outboard midplane.
+    let sibry = baxis * minor * minor / (2.0 * q * raxis);
+    let psi = |r: f64, z: f64| sibry * ((r - raxis).powi(2) + z * z) / (minor * minor);
+
+    let mut values = vec![rdim, zdim, raxis, rleft, 0.0];
+    values.extend([raxis, 0.0, 0.0, sibry, baxis]);
+    values.extend([1.0e6, 0.0, 0.0, raxis, 0.0]);
+    values.extend([0.0, 0.0, sibry, 0.0, 0.0]);
+    values.extend(std::iter::repeat_n(baxis * raxis, nw)); // fpol
+    values.extend(std::iter::repeat_n(0.0, 3 * nw)); // pres, ffprim, pprime
+    for j in 0..nh {
+        let z = -0.5 * zdim + zdim * j as f64 / (nh - 1) as f64;
+        for i in 0..nw {
+            let r = rleft + rdim * i as f64 / (nw - 1) as f64;
+            values.push(psi(r, z));
+        }
+    }
+    values.extend(std::iter::repeat_n(q, nw)); // qpsi
+
+    let mut text = format!("{:<48}{:>4}{:>4}{:>4}\n", "  CIRCULAR TEST", 0, nw, nh);
+    for line in values.chunks(5) {
+        line.iter()
+            .for_each(|x| text.push_str(&format!("{:>16.9E}", x)));
+        text.push('\n');
+    }
+    text.push_str("   0   0\n");
+
+    std::fs::write(&path, text).unwrap();
+    path
+}
+
+/// Creates a booz_xform output file with 4 surfaces and the (0, 0) and (1, 0) harmonics, plus a
+/// (1, 5) harmonic of amplitude `helical`.
+pub(crate) fn boozmn_path(filename: &str, helical: f64) -> Result<PathBuf, netcdf::Error> {
+    let path = std::env::temp_dir().join(filename);
+    let mut f = netcdf::create(&path)?;
+
+    let ns = 5;
+    let jlist = [2, 3, 4, 5];
+    let s: Vec<f64> = jlist.iter().map(|&j| (j as f64 - 1.5) / 4.0).collect();
+
+    f.add_dimension("radius", ns)?;
+    f.add_dimension("comput_surfs", jlist.len())?;
+    f.add_dimension("mn_mode", 3)?;
+
+    f.add_variable::<i32>("ns_b", &[])?
+        .put_value(ns as i32, ..)?;
+    f.add_variable::<i32>("nfp_b", &[])?.put_value(5, ..)?;
+    f.add_variable::<i32>("jlist", &["comput_surfs"])?
+        .put_values(&jlist, ..)?;
+    f.add_variable::<i32>("ixm_b", &["mn_mode"])?
+        .put_values(&[0, 1, 1], ..)?;
+    f.add_variable::<i32>("ixn_b", &["mn_mode"])?
+        .put_values(&[0, 0, 5], ..)?;
+
+    let radial = |values: [f64; 4]| [0.0, values[0], values[1], values[2], values[3]];
+    let iota = radial([0.5; 4]);
+    let buco = radial(
+        s.iter()
+            .map(|s| 0.01 * s)
+            .collect::<Vec<_>>()
+            .try_into()
+            .unwrap(),
+    );
+    let bvco = radial([3.3; 4]);
+    let phi: Vec<f64> = (0..ns).map(|j| 0.1 * j as f64 / 4.0).collect();
+    f.add_variable::<f64>("iota_b", &["radius"])?
+        .put_values(&iota, ..)?;
+    f.add_variable::<f64>("buco_b", &["radius"])?
+        .put_values(&buco, ..)?;
+    f.add_variable::<f64>("bvco_b", &["radius"])?
+        .put_values(&bvco, ..)?;
+    f.add_variable::<f64>("phi_b", &["radius"])?
+        .put_values(&phi, ..)?;
+
+    let harmonics = |coeffs: &dyn Fn(f64) -> [f64; 3]| {
+        let flat: Vec<f64> = s.iter().flat_map(|&s| coeffs(s)).collect();
+        Array::<f64, Ix2>::from_shape_vec((4, 3), flat).unwrap()
+    };
+    let bmnc = harmonics(&|s| [2.0, -0.2 * s.sqrt(), helical]);
+    let rmnc = harmonics(&|s| [1.65, 0.5 * s.sqrt(), 0.0]);
+    let zmns = harmonics(&|s| [0.0, 0.5 * s.sqrt(), 0.0]);
+    for (name, values) in [("bmnc_b", bmnc), ("rmnc_b", rmnc), ("zmns_b", zmns)] {
+        f.add_variable::<f64>(name, &["comput_surfs", "mn_mode"])?
+            .put(values.view(), (.., ..))?;
+    }
+
+    Ok(path)
+}
+
+/// Creates a phony NetCDF file with 3 time slices at t = 0.5, 1.0, 1.5. The currents, the
+/// magnetic field and `Baxis` have a leading time dimension, and are multiplied by the slice's
+/// index + 1.
+pub(crate) fn time_sliced_netcdf_path(filename: &str) -> Result<PathBuf, netcdf::Error> {
+    let schema = NcSchema::default();
+    let path = std::env::temp_dir().join(filename);
+    let mut f = netcdf::create(&path)?;
+
+    let (time_len, psi_len, theta_len) = (3, 2, 3);
+    f.add_dimension(&schema.time, time_len)?;
+    f.add_dimension(&schema.psi, psi_len)?;
+    f.add_dimension(&schema.theta, theta_len)?;
+
+    f.add_variable::<f64>(&schema.time, &[&schema.time])?
+        .put_values(&[0.5, 1.0, 1.5], ..)?;
+    f.add_variable::<f64>(&schema.baxis, &[&schema.time])?
+        .put_values(&[1.0, 2.0, 3.0], ..)?;
+    f.add_variable::<f64>(&schema.raxis, &[])?
+        .put_values(&[1.65], ..)?;
+    f.add_variable::<f64>(&schema.psi, &[&schema.psi])?
+        .put_values(&[0.1, 0.2], ..)?;
+    f.add_variable::<f64>(&schema.theta, &[&schema.theta])?
+        .put_values(&[0.0, 2.0 * PI / 3.0, 4.0 * PI / 3.0], ..)?;
+
+    let scaled = |values: &[f64]| -> Vec<f64> {
+        (1..=time_len)
+            .flat_map(|t| values.iter().map(move |v| v * t as f64))
+            .collect()
+    };
+    let i = Array::from_shape_vec((time_len, psi_len), scaled(&[0.0, 0.1])).unwrap();
+    let g = Array::from_shape_vec((time_len, psi_len), scaled(&[0.2, 0.1])).unwrap();
+    let b = Array::from_shape_vec(
+        (time_len, psi_len, theta_len),
+        scaled(&[0.1, 0.2, 0.3, 0.4, 0.5, 0.6]),
+    )
+    .unwrap();
+    f.add_variable::<f64>(&schema.i, &[&schema.time, &schema.psi])?
+        .put(i.view(), (.., ..))?;
+    f.add_variable::<f64>(&schema.g, &[&schema.time, &schema.psi])?
+        .put(g.view(), (.., ..))?;
+    f.add_variable::<f64>(&schema.b_field, &[&schema.time, &schema.psi, &schema.theta])?
+        .put(b.view(), (.., .., ..))?;
+
+    Ok(path)
+}
+
+/// Adds provenance global attributes, and `units` and `long_name` attributes to the magnetic
+/// field, of an existing phony NetCDF file.
+pub(crate) fn add_metadata(path: &Path, schema: &NcSchema) -> Result<(), netcdf::Error> {
+    let mut f = netcdf::append(path)?;
+
+    f.add_attribute("shot", 43210)?;
+    f.add_attribute("time", 2.5)?;
+    f.add_attribute("code", "EFIT")?;
+    f.add_attribute("version", "1.2.3")?;
+    f.add_attribute("date", "2024-05-01")?;
+
+    let mut b = f.variable_mut(&schema.b_field).unwrap();
+    b.put_attribute("units", "normalised")?;
+    b.put_attribute("long_name", "magnetic field strength")?;
+    Ok(())
+}
@@ -1,11 +1,25 @@
-use tokamak_netcdf::NcData;
+use tokamak_netcdf::{
+    Accelerator, Akima, BoozerGrid, CircularTokamak, Cubic, DomainPolicy, Interpolation, Linear,
+    NcData, NcError, NcSchema, PsiGrid, Steffen, ThetaGrid,
+};
+#[cfg(feature = "netcdf")]
+use tokamak_netcdf::{
+    AttributeValue, AxisExtrapolation, AxisSchema, Cocos, ImasEquilibrium, TimeSlices,
+};
 
 mod common;
 
 #[test]
+#[cfg(feature = "netcdf")]
 fn test_nc_data_creation() -> Result<(), netcdf::Error> {
     let path = &common::phony_netcdf_path()?;
     let nc_data = NcData::open(path.into()).unwrap();
+    assert!(nc_data.profiles.is_none());
+    assert!(nc_data.geometry.is_none());
+    assert_eq!(
+        nc_data.bfield.b.dim(),
+        (nc_data.coords.psi_len, nc_data.coords.theta_len)
+    );
 
     // test for functionality
     let _ = format!("{:?}", nc_data);
@@ -14,3 +28,1137 @@ fn test_nc_data_creation() -> Result<(), netcdf::Error> {
     std::fs::remove_file(path).unwrap();
     Ok(())
 }
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_from_bytes() -> Result<(), NcError> {
+    let bytes = common::phony_netcdf_bytes().unwrap();
+    let nc_data = NcData::from_bytes(&bytes)?;
+    assert_eq!(nc_data.path, std::path::PathBuf::new());
+    assert_eq!(nc_data.bfield.b.dim(), (3, 3));
+    assert_eq!(nc_data.metadata.checksum.as_deref().map(str::len), Some(64));
+
+    let schema = NcSchema::default().with_psi("psi_tor");
+    assert!(matches!(
+        NcData::from_bytes_with(&bytes, &schema),
+        Err(NcError::VariableNotFound(_))
+    ));
+    assert!(matches!(
+        NcData::from_bytes(b"not a netcdf file"),
+        Err(NcError::LibraryError { .. })
+    ));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_creation_with_schema() -> Result<(), netcdf::Error> {
+    let schema = NcSchema::default()
+        .with_psi("psi_tor")
+        .with_theta("theta_b")
+        .with_b_field("bmod")
+        .with_g("g")
+        .with_i("I")
+        .with_baxis("B0")
+        .with_raxis("R0");
+    let path = &common::phony_netcdf_path_with(
+        common::unique_temp_path("phony_schema").to_str().unwrap(),
+        &schema,
+    )?;
+
+    let nc_data = NcData::open_with(path.into(), &schema).unwrap();
+    assert_eq!(nc_data.coords.psi_len, 3);
+    assert_eq!(nc_data.coords.theta_len, 3);
+
+    // The default names are not present in the file.
+    let err = NcData::open(path.into()).unwrap_err();
+    assert!(matches!(err, NcError::VariableNotFound(_)));
+    std::fs::remove_file(path).unwrap();
+
+    // Each preset opens its pipeline's files, and only those.
+    for (preset, other) in [
+        (NcSchema::psi_tor(), NcSchema::psi_tor_bmod()),
+        (NcSchema::psi_tor_bmod(), NcSchema::psi_tor()),
+    ] {
+        let path = common::unique_temp_path("phony_preset");
+        common::phony_netcdf_path_with(path.to_str().unwrap(), &preset)?;
+        let nc_data = NcData::open_with(path.clone(), &preset).unwrap();
+        assert_eq!(nc_data.schema, preset);
+        assert!(matches!(
+            NcData::open_with(path.clone(), &other),
+            Err(NcError::VariableNotFound(_))
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_optional_variables() -> Result<(), netcdf::Error> {
+    let schema = NcSchema::default();
+    let path = &common::phony_netcdf_path_with(
+        common::unique_temp_path("phony_optional").to_str().unwrap(),
+        &schema,
+    )?;
+    common::add_optional_variables(path, &schema)?;
+
+    let nc_data = NcData::open(path.into()).unwrap();
+
+    let profiles = nc_data.profiles.as_ref().unwrap();
+    let q = profiles.q.as_ref().unwrap();
+    assert_eq!(q.len(), nc_data.coords.psi_len);
+    assert_eq!(q[0], q[1]);
+    assert!(profiles.psip.is_none());
+
+    let geometry = nc_data.geometry.as_ref().unwrap();
+    assert_eq!(geometry.r.dim(), nc_data.bfield.b.dim());
+    assert_eq!(geometry.z.dim(), nc_data.bfield.b.dim());
+
+    let _ = format!("{:#?}", nc_data);
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_shape_mismatch() -> Result<(), netcdf::Error> {
+    let schema = NcSchema::default();
+    let path = &common::phony_netcdf_path_with(
+        common::unique_temp_path("phony_mismatch").to_str().unwrap(),
+        &schema,
+    )?;
+    {
+        let mut f = netcdf::append(path)?;
+        f.add_variable::<f64>("i_theta", &[&schema.theta])?
+            .put_values(&[0.0, 0.1, 0.2], ..)?;
+        f.add_variable::<f64>("b_psi_psi", &[&schema.psi, &schema.psi])?;
+    }
+
+    let err = NcData::open_with(path.into(), &schema.clone().with_i("i_theta")).unwrap_err();
+    assert!(matches!(err, NcError::ShapeMismatch { .. }));
+
+    let err = NcData::open_with(path.into(), &schema.with_b_field("b_psi_psi")).unwrap_err();
+    assert!(matches!(err, NcError::DimensionMismatch { .. }));
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_transposed() -> Result<(), NcError> {
+    let schema = NcSchema::default();
+    let path = common::phony_netcdf_path().unwrap();
+    let nc_data = NcData::open(path.clone())?;
+    {
+        // Written in (θ, ψ) order, as by a Fortran-ordered tool.
+        let mut f = netcdf::append(&path).unwrap();
+        let b_transposed = nc_data.bfield.b.slice(ndarray::s![1.., ..]).t().to_owned();
+        f.add_variable::<f64>("b_transposed", &[&schema.theta, &schema.psi])
+            .unwrap()
+            .put(b_transposed.as_standard_layout().view(), (.., ..))
+            .unwrap();
+    }
+
+    let transposed = NcData::open_with(path.clone(), &schema.with_b_field("b_transposed"))?;
+    assert_eq!(transposed.bfield.b, nc_data.bfield.b);
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_axis_extrapolation() -> Result<(), netcdf::Error> {
+    let schema = NcSchema::default();
+    let path = &common::phony_netcdf_path_with(
+        common::unique_temp_path("phony_axis").to_str().unwrap(),
+        &schema,
+    )?;
+
+    let linear = schema.clone().with_axis(AxisSchema {
+        g: AxisExtrapolation::Linear,
+        ..Default::default()
+    });
+    let nc_data = NcData::open_with(path.into(), &linear).unwrap();
+    assert!((nc_data.currents.g[0] - 0.3).abs() < 1e-12);
+    assert_eq!(nc_data.coords.psi[0], 0.0);
+
+    let no_axis = schema.clone().with_axis(AxisSchema::none());
+    let nc_data = NcData::open_with(path.into(), &no_axis).unwrap();
+    assert_eq!(nc_data.coords.psi_len, 2);
+    assert_eq!(nc_data.bfield.b.dim(), (2, 3));
+
+    // Only some of the quantities have an axis value.
+    let partial = schema.clone().with_axis(AxisSchema {
+        b_field: AxisExtrapolation::None,
+        ..Default::default()
+    });
+    let err = NcData::open_with(path.into(), &partial).unwrap_err();
+    assert!(matches!(err, NcError::ShapeMismatch { .. }));
+
+    // A ψ grid starting on the axis gets no second axis knot.
+    netcdf::append(path)?
+        .variable_mut(&schema.psi)
+        .unwrap()
+        .put_values(&[0.0, 0.2], ..)?;
+    let nc_data = NcData::open(path.into()).unwrap();
+    assert_eq!(nc_data.coords.psi.to_vec(), [0.0, 0.2]);
+    assert!(!nc_data.coords.extrapolated_axis);
+    assert!(nc_data.validate().is_valid());
+    let (i, g, di, dg) = nc_data.currents.eval(0.1).unwrap();
+    assert!([i, g, di, dg].iter().all(|x| x.is_finite()));
+    let (b, ..) = nc_data.bfield_spline().unwrap().eval(0.1, 1.0).unwrap();
+    assert!(b.is_finite());
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_validation() -> Result<(), netcdf::Error> {
+    let path = &common::phony_netcdf_path_with(
+        common::unique_temp_path("phony_validation")
+            .to_str()
+            .unwrap(),
+        &NcSchema::default(),
+    )?;
+    let mut nc_data = NcData::open(path.into()).unwrap();
+    assert!(nc_data.validate().is_valid());
+
+    nc_data.coords.psi[2] = 0.05;
+    nc_data.currents.g[1] = f64::NAN;
+    nc_data.bfield.b[[1, 1]] = -0.2;
+    nc_data.bfield.b[[1, 2]] = f64::INFINITY;
+
+    let report = nc_data.validate();
+    assert_eq!(report.errors.len(), 4);
+    assert!(matches!(report.errors[0], NcError::NonMonotonic(_)));
+    assert!(matches!(
+        report.errors[1],
+        NcError::NonFinite { count: 1, .. }
+    ));
+    assert!(matches!(
+        report.errors[2],
+        NcError::NonFinite { count: 1, .. }
+    ));
+    assert!(matches!(
+        report.errors[3],
+        NcError::NonPositive { count: 1, .. }
+    ));
+    let _ = format!("{}", report);
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_write_round_trip() -> Result<(), netcdf::Error> {
+    let schema = NcSchema::default().with_psi("psi_tor");
+    let path = &common::phony_netcdf_path_with(
+        common::unique_temp_path("phony_write").to_str().unwrap(),
+        &schema,
+    )?;
+    common::add_optional_variables(path, &schema)?;
+    let written_path = &common::unique_temp_path("phony_written");
+
+    for axis in [AxisSchema::default(), AxisSchema::none()] {
+        let nc_data = NcData::open_with(path.into(), &schema.clone().with_axis(axis)).unwrap();
+        nc_data.write(written_path).unwrap();
+        let read_back = NcData::open_with(written_path.into(), &nc_data.schema).unwrap();
+
+        assert_eq!(nc_data.scalars.baxis, read_back.scalars.baxis);
+        assert_eq!(nc_data.scalars.raxis, read_back.scalars.raxis);
+        assert_eq!(nc_data.scalars.psi_wall, read_back.scalars.psi_wall);
+        assert_eq!(nc_data.coords.psi, read_back.coords.psi);
+        assert_eq!(nc_data.coords.theta, read_back.coords.theta);
+        assert_eq!(nc_data.currents.i, read_back.currents.i);
+        assert_eq!(nc_data.currents.g, read_back.currents.g);
+        assert_eq!(nc_data.bfield.b, read_back.bfield.b);
+
+        let (profiles, read_profiles) = (nc_data.profiles.unwrap(), read_back.profiles.unwrap());
+        assert_eq!(profiles.q, read_profiles.q);
+        assert!(read_profiles.psip.is_none());
+
+        let (geometry, read_geometry) = (nc_data.geometry.unwrap(), read_back.geometry.unwrap());
+        assert_eq!(geometry.r, read_geometry.r);
+        assert_eq!(geometry.z, read_geometry.z);
+    }
+
+    // The axis value is not written.
+    let f = netcdf::open(written_path)?;
+    assert_eq!(f.variable("psi_tor").unwrap().len(), 2);
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(written_path).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_from_eqdsk() -> Result<(), NcError> {
+    let path = common::circular_gfile_path("circular_eqdsk.geqdsk", 2.0, 1.65, 2.0);
+    let grid = BoozerGrid {
+        psi_len: 32,
+        theta_len: 64,
+        psi_n_max: 0.99,
+    };
+    let nc_data = NcData::open_eqdsk(path.clone(), &grid)?;
+    assert!(nc_data.validate().is_valid());
+    assert_eq!(nc_data.coords.psi_len, 33);
+    assert_eq!(nc_data.coords.theta_len, 64);
+    assert!((nc_data.scalars.baxis - 2.0).abs() < 1e-12);
+    assert!((nc_data.scalars.raxis - 1.65).abs() < 1e-12);
+
+    // With a constant q, the toroidal flux is q times the poloidal flux.
+    let profiles = nc_data.profiles.as_ref().unwrap();
+    let psip = profiles.psip.as_ref().unwrap();
+    let psi_wall = nc_data.scalars.psi_wall;
+    assert!((psi_wall - 2.0 * psip.last().unwrap()).abs() < 1e-9);
+    assert!(nc_data.currents.g.iter().all(|&g| (g - 1.0).abs() < 1e-12));
+    assert!(
+        nc_data
+            .currents
+            .i
+            .windows(2)
+            .into_iter()
+            .all(|w| w[1] > w[0])
+    );
+
+    // The field is weakest on the outboard midplane, where R is largest.
+    let last = nc_data.coords.psi_len - 1;
+    let b = nc_data.bfield.b.row(last);
+    let r = nc_data.geometry.as_ref().unwrap().r.row(last);
+    assert!(b[0] < b[32]);
+    assert!(r[0] > 1.0 && r[32] < 1.0);
+    assert!((b[0] * r[0] - 1.0).abs() < 1e-2);
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_from_imas() -> Result<(), NcError> {
+    let grid = BoozerGrid {
+        psi_len: 32,
+        theta_len: 64,
+        psi_n_max: 0.99,
+    };
+    let gfile_path = common::circular_gfile_path("imas_reference.geqdsk", 2.0, 1.65, 2.0);
+    let reference = NcData::open_eqdsk(gfile_path.clone(), &grid)?;
+
+    // The profiles are interpolated exactly, so that both routes map the same equilibrium.
+    for dd_version in ["3.41.0", "4.0.0"] {
+        let path = common::imas_netcdf_path(dd_version).unwrap();
+        let nc_data = NcData::open_imas(path.clone(), 0, &grid)?;
+        assert_eq!(nc_data.time, Some(0.1));
+        assert_eq!(nc_data.coords.psi_len, reference.coords.psi_len);
+        assert!((nc_data.scalars.psi_wall - reference.scalars.psi_wall).abs() < 1e-12);
+        let close = |a: &ndarray::Array2<f64>, b: &ndarray::Array2<f64>| {
+            a.iter().zip(b.iter()).all(|(x, y)| (x - y).abs() < 1e-9)
+        };
+        assert!(close(&nc_data.bfield.b, &reference.bfield.b));
+        assert!(
+            nc_data
+                .currents
+                .i
+                .iter()
+                .zip(reference.currents.i.iter())
+                .all(|(x, y)| (x - y).abs() < 1e-9)
+        );
+        assert_eq!(
+            nc_data.metadata.attributes["data_dictionary_version"],
+            AttributeValue::Str(dd_version.into())
+        );
+
+        let slice = NcData::open_imas(path.clone(), 1, &grid)?;
+        assert_eq!(slice.time, Some(0.2));
+        assert!((slice.scalars.baxis - 3.0).abs() < 1e-12);
+        assert!(close(&slice.bfield.b, &nc_data.bfield.b));
+        assert!(matches!(
+            NcData::open_imas(path.clone(), 2, &grid),
+            Err(NcError::TimeIndexOutOfRange { index: 2, len: 2 })
+        ));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    // Reading DD4 data as DD3 flips the sign of ψ, which the mapping does not depend on.
+    let path = common::imas_netcdf_path("4.0.0").unwrap();
+    let imas = ImasEquilibrium::open(&path, 0)?;
+    let as_dd3 = ImasEquilibrium::open_with_cocos(&path, 0, Cocos::IMAS_DD3)?;
+    assert!(imas.psi_boundary < 0.0);
+    assert_eq!(imas.to_cocos(Cocos::IMAS_DD3).psirz, -&as_dd3.psirz);
+    assert!((imas.to_cocos(Cocos::EQDSK).psi_boundary - 0.125 / 1.65).abs() < 1e-12);
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(gfile_path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_from_imas_access_layer() -> Result<(), NcError> {
+    let grid = BoozerGrid {
+        psi_len: 16,
+        theta_len: 32,
+        psi_n_max: 0.99,
+    };
+    for dd_version in ["3.41.0", "4.0.0"] {
+        let netcdf_path = common::imas_netcdf_path(dd_version).unwrap();
+        let hdf5_path = common::imas_hdf5_path(dd_version).unwrap();
+
+        // Without the padding, both layouts hold the same equilibrium, in the same convention.
+        let imas = ImasEquilibrium::open(&hdf5_path, 1)?;
+        let reference = ImasEquilibrium::open(&netcdf_path, 1)?;
+        assert_eq!(imas.cocos, reference.cocos);
+        assert_eq!((imas.psi.len(), imas.r.len()), (9, 65));
+        assert_eq!(imas.psirz, reference.psirz);
+        assert_eq!((imas.f, imas.q), (reference.f, reference.q));
+        assert_eq!((imas.time, imas.b0), (0.2, 3.0));
+
+        let nc_data = NcData::open_imas(hdf5_path.clone(), 0, &grid)?;
+        let reference = NcData::open_imas(netcdf_path.clone(), 0, &grid)?;
+        assert_eq!(nc_data.bfield.b, reference.bfield.b);
+        assert_eq!(nc_data.currents.i, reference.currents.i);
+        assert_eq!(
+            nc_data.metadata.attributes["data_dictionary_version"],
+            AttributeValue::Str(dd_version.into())
+        );
+        std::fs::remove_file(netcdf_path).unwrap();
+        std::fs::remove_file(hdf5_path).unwrap();
+    }
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_from_boozmn() -> Result<(), NcError> {
+    let path = common::boozmn_path(
+        common::unique_temp_path("axisymmetric_boozmn")
+            .to_str()
+            .unwrap(),
+        0.0,
+    )
+    .unwrap();
+    let nc_data = NcData::open_boozmn(path.clone(), 16)?;
+    assert!(nc_data.validate().is_valid());
+    assert_eq!(nc_data.coords.psi_len, 5);
+    assert_eq!(nc_data.coords.theta_len, 16);
+    assert_eq!(nc_data.scalars.baxis, 2.0);
+    assert_eq!(nc_data.scalars.raxis, 1.65);
+
+    // B = 1 - 0.1√s cosθ, normalised.
+    let s_last = 3.5 / 4.0_f64;
+    let b = nc_data.bfield.b.row(4);
+    assert!((b[0] - (1.0 - 0.1 * s_last.sqrt())).abs() < 1e-12);
+    assert!((b[8] - (1.0 + 0.1 * s_last.sqrt())).abs() < 1e-12);
+    assert!((nc_data.currents.g[4] - 1.0).abs() < 1e-12);
+
+    let profiles = nc_data.profiles.as_ref().unwrap();
+    assert!(profiles.q.as_ref().unwrap().iter().all(|&q| q == 2.0));
+    let psip = profiles.psip.as_ref().unwrap();
+    assert!((psip[4] - 0.5 * nc_data.coords.psi[4]).abs() < 1e-12);
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_from_non_axisymmetric_boozmn() -> Result<(), NcError> {
+    let path = common::boozmn_path(
+        common::unique_temp_path("helical_boozmn").to_str().unwrap(),
+        0.05,
+    )
+    .unwrap();
+    let boozmn = tokamak_netcdf::Boozmn::open(&path)?;
+    assert!(!boozmn.is_axisymmetric());
+    assert!(matches!(
+        boozmn.to_nc_data(16),
+        Err(NcError::NonAxisymmetric)
+    ));
+
+    // The helical harmonic adds 0.05 cos(θ - 5ζ).
+    let theta = ndarray::array![0.0];
+    let b = boozmn.b_field(theta.view(), 0.0);
+    assert!((b[[0, 0]] - (2.0 - 0.2 * (0.125_f64).sqrt() + 0.05)).abs() < 1e-12);
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_time_slices() -> Result<(), NcError> {
+    let path =
+        common::time_sliced_netcdf_path(common::unique_temp_path("time_sliced").to_str().unwrap())
+            .unwrap();
+
+    let nc_data = NcData::open_slice(path.clone(), 1)?;
+    assert_eq!(nc_data.time, Some(1.0));
+    assert_eq!(nc_data.scalars.baxis, 2.0);
+    assert_eq!(nc_data.scalars.raxis, 1.65);
+    assert_eq!(nc_data.bfield.b.dim(), (3, 3));
+    assert_eq!(nc_data.bfield.b[[1, 0]], 0.2);
+    assert_eq!(nc_data.currents.g[2], 0.2);
+
+    let slices = TimeSlices::open(path.clone())?;
+    assert_eq!(slices.len(), 3);
+    assert_eq!(slices.nearest(1.2), 1);
+    assert_eq!(slices.nearest(-4.0), 0);
+    assert_eq!(slices.get_nearest(1.4)?.scalars.baxis, 3.0);
+    let baxis: Vec<f64> = slices
+        .iter()
+        .map(|nc_data| nc_data.map(|nc_data| nc_data.scalars.baxis))
+        .collect::<Result<_, _>>()?;
+    assert_eq!(baxis, vec![1.0, 2.0, 3.0]);
+
+    assert!(matches!(
+        NcData::open_slice(path.clone(), 3),
+        Err(NcError::TimeIndexOutOfRange { index: 3, len: 3 })
+    ));
+    // The whole file is not a single equilibrium.
+    assert!(NcData::open(path.clone()).is_err());
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_metadata() -> Result<(), NcError> {
+    let schema = NcSchema::default();
+    let path = common::phony_netcdf_path_with(
+        common::unique_temp_path("metadata").to_str().unwrap(),
+        &schema,
+    )
+    .unwrap();
+    common::add_metadata(&path, &schema).unwrap();
+    // Beyond 2^53, which f64 cannot represent exactly.
+    let run_id = (1i64 << 53) + 1;
+    netcdf::append(&path)
+        .unwrap()
+        .add_attribute("run_id", run_id)
+        .unwrap();
+
+    let nc_data = NcData::open(path.clone())?;
+    let metadata = &nc_data.metadata;
+    assert_eq!(metadata.shot, Some(43210));
+    assert_eq!(metadata.time, Some(2.5));
+    assert_eq!(metadata.code.as_deref(), Some("EFIT"));
+    assert_eq!(metadata.version.as_deref(), Some("1.2.3"));
+    assert_eq!(metadata.date.as_deref(), Some("2024-05-01"));
+    assert_eq!(metadata.units(&schema.b_field), Some("normalised"));
+    assert_eq!(metadata.units(&schema.psi), None);
+    assert_eq!(metadata.attributes["shot"], AttributeValue::Int(43210));
+    assert_eq!(
+        metadata.attributes["run_id"],
+        AttributeValue::Longlong(run_id)
+    );
+    assert_eq!(
+        metadata.attributes["code"],
+        AttributeValue::Str("EFIT".into())
+    );
+    let checksum = metadata.checksum.as_deref().unwrap();
+    assert_eq!(checksum.len(), 64);
+
+    // Provenance survives a round trip, but the checksum is the new file's.
+    let written = common::unique_temp_path("metadata_written");
+    nc_data.write(&written)?;
+    let reopened = NcData::open(written.clone())?;
+    assert_eq!(reopened.metadata.shot, Some(43210));
+    assert_eq!(reopened.metadata.code.as_deref(), Some("EFIT"));
+    assert_eq!(reopened.metadata.attributes, metadata.attributes);
+    let file = netcdf::open(&written).unwrap();
+    let value = |name: &str| file.attribute(name).unwrap().value().unwrap();
+    assert_eq!(value("shot"), netcdf::AttributeValue::Int(43210));
+    assert_eq!(value("run_id"), netcdf::AttributeValue::Longlong(run_id));
+    assert_eq!(value("time"), netcdf::AttributeValue::Double(2.5));
+    assert_eq!(
+        reopened.metadata.variables[&schema.b_field]
+            .long_name
+            .as_deref(),
+        Some("magnetic field strength")
+    );
+    assert_ne!(reopened.metadata.checksum.as_deref(), Some(checksum));
+
+    std::fs::remove_file(path).unwrap();
+    std::fs::remove_file(written).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_analytic_fixture() -> Result<(), NcError> {
+    let tokamak = CircularTokamak {
+        psi_len: 50,
+        theta_len: 32,
+        ..Default::default()
+    };
+    let path = common::unique_temp_path("analytic");
+    tokamak.write(&path)?;
+
+    let nc_data = NcData::open(path.clone())?;
+    let expected = tokamak.to_nc_data()?;
+    assert!(nc_data.validate().is_valid());
+    assert_eq!(nc_data.bfield.b.dim(), (51, 32));
+    assert_eq!(nc_data.coords.psi, expected.coords.psi);
+    assert_eq!(nc_data.currents.i, expected.currents.i);
+    assert_eq!(nc_data.bfield.b, expected.bfield.b);
+
+    // B = 1 - ε cos θ, with ψ = ε²/2.
+    let epsilon = (2.0 * nc_data.scalars.psi_wall).sqrt();
+    assert!((epsilon - 0.5 / 1.65).abs() < 1e-12);
+    assert!((nc_data.bfield.b[[50, 0]] - (1.0 - epsilon)).abs() < 1e-12);
+    assert!((nc_data.bfield.b[[50, 16]] - (1.0 + epsilon)).abs() < 1e-12);
+
+    // I = ε²/q, and q spans [q0, qa].
+    let q = nc_data.profiles.as_ref().unwrap().q.as_ref().unwrap();
+    assert!((q[1] - (1.1 + 2.4 / 50.0)).abs() < 1e-12);
+    assert!((q[50] - 3.5).abs() < 1e-12);
+    assert!((nc_data.currents.i[50] - epsilon * epsilon / 3.5).abs() < 1e-12);
+
+    let _ = format!("{:#?}", nc_data);
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_resample() -> Result<(), NcError> {
+    let coarse = CircularTokamak {
+        psi_len: 40,
+        theta_len: 48,
+        ..Default::default()
+    }
+    .to_nc_data()?;
+    let fine = CircularTokamak {
+        psi_len: 160,
+        theta_len: 256,
+        ..Default::default()
+    }
+    .to_nc_data()?;
+
+    // Both sources on the same mesh agree up to the interpolation error, which is largest
+    // between the axis and the first surface, where B varies as √ψ.
+    let (psi_grid, theta_grid) = (PsiGrid::UniformSqrt(30), ThetaGrid::Uniform(37));
+    let coarse = coarse.resample(&psi_grid, &theta_grid)?;
+    let fine = fine.resample(&psi_grid, &theta_grid)?;
+    assert!(coarse.validate().is_valid());
+    assert_eq!(coarse.coords.psi, fine.coords.psi);
+    assert_eq!(coarse.bfield.b.dim(), (30, 37));
+    let max_diff = (&coarse.bfield.b - &fine.bfield.b)
+        .iter()
+        .fold(0.0_f64, |max, d| max.max(d.abs()));
+    assert!(max_diff < 2e-2);
+
+    // √ψ is uniform, starting on the axis.
+    let sqrt_psi = coarse.coords.psi.mapv(f64::sqrt);
+    assert_eq!(coarse.coords.psi[0], 0.0);
+    assert!((sqrt_psi[2] - 2.0 * sqrt_psi[1]).abs() < 1e-12);
+    assert_eq!(coarse.scalars.psi_wall, *coarse.coords.psi.last().unwrap());
+
+    // Resampling onto the same grid changes nothing.
+    let same = coarse.resample(
+        &PsiGrid::Values(coarse.coords.psi.clone()),
+        &ThetaGrid::Values(coarse.coords.theta.clone()),
+    )?;
+    assert!(
+        (&same.bfield.b - &coarse.bfield.b)
+            .iter()
+            .all(|d| d.abs() < 1e-12)
+    );
+
+    let beyond = PsiGrid::Values(ndarray::array![0.1, 1.0]);
+    assert!(matches!(
+        coarse.resample(&beyond, &theta_grid),
+        Err(NcError::InvalidGrid(_))
+    ));
+
+    // Minimal grids can still be formatted.
+    for (psi_len, theta_len) in [(1, 1), (1, 5), (2, 2), (3, 4), (5, 1)] {
+        let minimal =
+            coarse.resample(&PsiGrid::Uniform(psi_len), &ThetaGrid::Uniform(theta_len))?;
+        assert_eq!(minimal.bfield.b.dim(), (psi_len, theta_len));
+        assert!(format!("{minimal:?}").contains("Bfield"));
+    }
+    let narrow = coarse.subset_psi(0.0, coarse.coords.psi[1])?;
+    let decimated = narrow.decimate_theta(19)?;
+    assert_eq!(decimated.bfield.b.dim(), (2, 2));
+    assert!(format!("{decimated:?}").contains("Bfield"));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_resample_write_round_trip() -> Result<(), NcError> {
+    let nc_data = CircularTokamak::default().to_nc_data()?;
+    let psi_wall = nc_data.scalars.psi_wall;
+    let path = &common::unique_temp_path("resampled_written");
+
+    // A grid that does not start on the axis gets no axis value when reopened.
+    let psi = ndarray::Array1::linspace(0.1 * psi_wall, psi_wall, 12);
+    let resampled = nc_data.resample(&PsiGrid::Values(psi), &ThetaGrid::Uniform(16))?;
+    assert!(!resampled.coords.extrapolated_axis);
+    resampled.write(path)?;
+    let read_back = NcData::open_with(path.into(), &resampled.schema)?;
+    assert_eq!(resampled.coords.psi, read_back.coords.psi);
+    assert_eq!(resampled.bfield.b, read_back.bfield.b);
+    assert_eq!(resampled.currents.i, read_back.currents.i);
+
+    // Derived data keeps the provenance, but not the source file's checksum.
+    assert!(read_back.metadata.checksum.is_some());
+    let derived = [
+        read_back.resample(&PsiGrid::Uniform(8), &ThetaGrid::Uniform(8))?,
+        read_back.subset_psi(psi_wall * 0.5, psi_wall)?,
+        read_back.decimate_theta(2)?,
+    ];
+    for nc_data in derived {
+        assert_eq!(nc_data.metadata.checksum, None);
+        assert_eq!(nc_data.metadata.attributes, read_back.metadata.attributes);
+    }
+
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_subset_and_decimate() -> Result<(), NcError> {
+    let nc_data = CircularTokamak {
+        psi_len: 20,
+        theta_len: 64,
+        ..Default::default()
+    }
+    .to_nc_data()?;
+    let psi = &nc_data.coords.psi;
+
+    // Core only, including the axis.
+    let core = nc_data.subset_psi(0.0, psi[10])?;
+    assert!(core.validate().is_valid());
+    assert_eq!(core.coords.psi_len, 11);
+    assert_eq!(core.coords.psi_span, (0.0, psi[10]));
+    assert_eq!(core.scalars.psi_wall, psi[10]);
+    assert!(core.coords.extrapolated_axis);
+    assert_eq!(core.bfield.b.row(10), nc_data.bfield.b.row(10));
+    assert_eq!(core.currents.i.len(), 11);
+
+    // An annulus no longer has an axis value.
+    let annulus = nc_data.subset_psi(psi[5] - 1e-9, psi[15] + 1e-9)?;
+    assert_eq!(annulus.coords.psi_len, 11);
+    assert!(!annulus.coords.extrapolated_axis);
+    assert_eq!(annulus.bfield.b.row(0), nc_data.bfield.b.row(5));
+    let geometry = annulus.geometry.as_ref().unwrap();
+    assert_eq!(geometry.r.dim(), (11, 64));
+
+    // Reopening the written annulus does not add an axis value.
+    #[cfg(feature = "netcdf")]
+    {
+        let path = &common::unique_temp_path("annulus_written");
+        annulus.write(path)?;
+        let read_back = NcData::open_with(path.into(), &annulus.schema)?;
+        assert_eq!(read_back.coords.psi, annulus.coords.psi);
+        assert_eq!(read_back.bfield.b, annulus.bfield.b);
+        std::fs::remove_file(path).unwrap();
+    }
+
+    let decimated = nc_data.decimate_theta(3)?;
+    assert!(decimated.validate().is_valid());
+    assert_eq!(decimated.coords.theta_len, 22);
+    assert_eq!(decimated.coords.theta[1], nc_data.coords.theta[3]);
+    assert_eq!(decimated.bfield.b.column(2), nc_data.bfield.b.column(6));
+
+    assert!(matches!(
+        nc_data.subset_psi(psi[3] + 1e-9, psi[4] - 1e-9),
+        Err(NcError::InvalidGrid(_))
+    ));
+    assert!(matches!(
+        nc_data.decimate_theta(0),
+        Err(NcError::InvalidGrid(_))
+    ));
+    assert!(matches!(
+        nc_data.decimate_theta(64),
+        Err(NcError::InvalidGrid(_))
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_bfield_fourier() -> Result<(), NcError> {
+    let nc_data = CircularTokamak::default().to_nc_data()?;
+    let theta = &nc_data.coords.theta;
+    let fourier = nc_data.bfield_fourier(8)?;
+    assert_eq!(fourier.m_max(), 8);
+    assert_eq!(fourier.cos.dim(), (nc_data.coords.psi_len, 9));
+
+    // B = 1 - ε cos θ, with ε = √(2ψ).
+    for (k, psi) in nc_data.coords.psi.iter().enumerate() {
+        assert!((fourier.cos[[k, 0]] - 1.0).abs() < 1e-12);
+        assert!((fourier.cos[[k, 1]] + (2.0 * psi).sqrt()).abs() < 1e-12);
+        assert!(fourier.cos.row(k).iter().skip(2).all(|c| c.abs() < 1e-12));
+    }
+    assert!(fourier.is_up_down_symmetric(1e-12));
+
+    // The series reproduces B on the original grid, and its θ derivative.
+    let rebuilt = fourier.to_bfield(theta.view());
+    assert!(
+        (&rebuilt.b - &nc_data.bfield.b)
+            .iter()
+            .all(|d| d.abs() < 1e-12)
+    );
+    let dtheta = fourier.dtheta(theta.view());
+    let k = 50;
+    let epsilon = (2.0 * nc_data.coords.psi[k]).sqrt();
+    for (j, t) in theta.iter().enumerate() {
+        assert!((dtheta[[k, j]] - epsilon * t.sin()).abs() < 1e-12);
+    }
+
+    // Errors name the equilibrium's own θ variable.
+    let mut half = CircularTokamak::default().to_nc_data()?;
+    half.schema = NcSchema::psi_tor();
+    half.coords.theta.mapv_inplace(|t| t / 2.0);
+    assert!(matches!(
+        half.bfield_fourier(1),
+        Err(NcError::IncompletePeriod { name, .. }) if &*name == "theta_b"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_currents_eval() -> Result<(), NcError> {
+    let tokamak = CircularTokamak::default();
+    let nc_data = tokamak.to_nc_data()?;
+    let (psi, currents) = (&nc_data.coords.psi, &nc_data.currents);
+    assert!(nc_data.coords.extrapolated_axis);
+
+    // The splines pass through every knot, including the axis and the last surface.
+    for k in 0..nc_data.coords.psi_len {
+        let (i, g, _, dg) = currents.eval(psi[k])?;
+        assert!((i - currents.i[k]).abs() < 1e-14);
+        assert!((g - currents.g[k]).abs() < 1e-14);
+        assert!(dg.abs() < 1e-12);
+    }
+
+    // I = 2ψ/q with q = q0 + (qa - q0) ψ/ψa.
+    let psi_wall = nc_data.scalars.psi_wall;
+    let slope = (tokamak.qa - tokamak.q0) / psi_wall;
+    for x in [0.125, 0.375, 0.815] {
+        let psi = x * psi_wall;
+        let q = tokamak.q0 + slope * psi;
+        let (i, _, di, _) = currents.eval(psi)?;
+        assert!((i - 2.0 * psi / q).abs() < 1e-9);
+        assert!((di - (2.0 / q - 2.0 * psi * slope / (q * q))).abs() < 1e-6);
+    }
+
+    // The ends' pieces are extended.
+    let (i, _, di, _) = currents.eval(psi_wall)?;
+    let (outside, ..) = currents.eval(1.01 * psi_wall)?;
+    assert!((outside - i - di * 0.01 * psi_wall).abs() < 1e-6);
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_bfield_spline() -> Result<(), NcError> {
+    use std::f64::consts::TAU;
+
+    let nc_data = CircularTokamak::default().to_nc_data()?;
+    let spline = nc_data.bfield_spline()?;
+    let (psi, theta, b) = (
+        &nc_data.coords.psi,
+        &nc_data.coords.theta,
+        &nc_data.bfield.b,
+    );
+
+    // The spline passes through every grid point.
+    for (k, j) in [(0, 0), (1, 5), (50, 63), (100, 17)] {
+        assert!((spline.eval(psi[k], theta[j])?.0 - b[[k, j]]).abs() < 1e-14);
+    }
+
+    // B = 1 - ε cos θ, with ε = √(2ψ).
+    let psi_wall = nc_data.scalars.psi_wall;
+    for (x, t) in [(0.305, 0.3), (0.52, 2.0), (0.87, 4.1)] {
+        let psi = x * psi_wall;
+        let epsilon = (2.0 * psi).sqrt();
+        for theta in [t, t + TAU, t - 3.0 * TAU] {
+            let (b, db_dpsi, db_dtheta, d2b) = spline.eval(psi, theta)?;
+            assert!((b - (1.0 - epsilon * t.cos())).abs() < 1e-6);
+            assert!((db_dpsi + t.cos() / epsilon).abs() < 1e-3);
+            assert!((db_dtheta - epsilon * t.sin()).abs() < 1e-4);
+            assert!((d2b - t.sin() / epsilon).abs() < 1e-2);
+        }
+        let (a, b) = (spline.eval(psi, t)?, spline.eval(psi, t + TAU)?);
+        assert!((a.0 - b.0).abs() < 1e-12 && (a.2 - b.2).abs() < 1e-12);
+    }
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_interpolation_schemes() -> Result<(), NcError> {
+    let nc_data = CircularTokamak::default().to_nc_data()?;
+    let (coords, b) = (&nc_data.coords, &nc_data.bfield.b);
+    let schemes: [&dyn Interpolation; 4] = [&Linear, &Cubic, &Akima, &Steffen];
+
+    // Every scheme passes through the grid points.
+    for scheme in schemes {
+        let bfield = nc_data.bfield_spline_with(scheme)?;
+        let currents = nc_data.currents_spline_with(scheme)?;
+        for (k, j) in [(0, 0), (1, 5), (50, 63), (100, 17)] {
+            let (psi, theta) = (coords.psi[k], coords.theta[j]);
+            assert!((bfield.eval(psi, theta)?.0 - b[[k, j]]).abs() < 1e-14);
+            let (i, g, _, _) = currents.eval(psi)?;
+            assert!((i - nc_data.currents.i[k]).abs() < 1e-14);
+            assert!((g - nc_data.currents.g[k]).abs() < 1e-14);
+        }
+    }
+
+    // Linear interpolation is bilinear on each cell.
+    let linear = nc_data.bfield_spline_with(&Linear)?;
+    let (psi, theta) = (0.25 * coords.psi[3] + 0.75 * coords.psi[4], coords.theta[7]);
+    let expected = 0.25 * b[[3, 7]] + 0.75 * b[[4, 7]];
+    assert!((linear.eval(psi, theta)?.0 - expected).abs() < 1e-14);
+    assert!((linear.eval(psi, theta)?.1 - linear.eval(coords.psi[3], theta)?.1).abs() < 1e-12);
+
+    // The default splines are natural and periodic cubic splines.
+    let cubic = nc_data.bfield_spline_with(&Cubic)?;
+    let default = nc_data.bfield_spline()?;
+    assert_eq!(cubic.eval(0.01, 1.0)?, default.eval(0.01, 1.0)?);
+    let currents = nc_data.currents_spline_with(&Cubic)?;
+    assert_eq!(currents.eval(0.01)?, nc_data.currents.eval(0.01)?);
+
+    // Mismatched grids are reported with the schema's names.
+    let mut mismatched = nc_data.subset_psi(0.2 * coords.psi_span.1, coords.psi_span.1)?;
+    mismatched.schema = NcSchema::new().with_i("I_tor").with_b_field("bmod");
+    mismatched.coords = CircularTokamak::default().to_nc_data()?.coords;
+    assert!(matches!(
+        mismatched.currents_spline_with(&Cubic),
+        Err(NcError::ShapeMismatch { name, .. }) if &*name == "I_tor"
+    ));
+    assert!(matches!(
+        mismatched.bfield_spline(),
+        Err(NcError::ShapeMismatch { name, .. }) if &*name == "bmod"
+    ));
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_accelerator() -> Result<(), NcError> {
+    let nc_data = CircularTokamak::default().to_nc_data()?;
+    let bfield = nc_data.bfield_spline_with(&Steffen)?;
+    let currents = nc_data.currents_spline_with(&Steffen)?;
+    let psi_wall = nc_data.scalars.psi_wall;
+
+    // An orbit drifting outwards past the wall, with a jump back to the axis.
+    let mut acc = Accelerator::new();
+    for step in 0..4000 {
+        let psi = match step {
+            2000 => 0.0,
+            step => {
+                psi_wall * (0.1 + (step % 2000) as f64 * 5e-4 + 0.01 * (0.1 * step as f64).sin())
+            }
+        };
+        let theta = -3.0 + 0.02 * step as f64;
+        assert_eq!(currents.eval_with(psi, &mut acc)?, currents.eval(psi)?);
+        assert_eq!(
+            bfield.eval_with(psi, theta, &mut acc)?,
+            bfield.eval(psi, theta)?
+        );
+        assert_eq!(
+            nc_data.currents.eval_with(psi, &mut acc)?,
+            nc_data.currents.eval(psi)?
+        );
+    }
+    acc.reset();
+    assert_eq!(acc, Accelerator::default());
+    Ok(())
+}
+
+#[test]
+fn test_nc_data_domain_policy() -> Result<(), NcError> {
+    use DomainPolicy::*;
+
+    let nc_data = CircularTokamak::default().to_nc_data()?;
+    let coords = &nc_data.coords;
+    let bfield =
+        |policy| -> Result<_, NcError> { Ok(nc_data.bfield_spline()?.with_domain(policy)) };
+    let currents = |policy| -> Result<_, NcError> {
+        Ok(nc_data.currents_spline_with(&Cubic)?.with_domain(policy))
+    };
+    let (first, last) = coords.psi_span;
+    let (inside, beyond, theta) = (0.5 * last, 1.2 * last, 2.0);
+
+    // Inside the span, every policy gives the default results.
+    for policy in [Extend, Error, Clamp, Vacuum] {
+        assert_eq!(
+            bfield(policy)?.eval(inside, theta)?,
+            bfield(Extend)?.eval(inside, theta)?
+        );
+        assert_eq!(currents(policy)?.eval(last)?, nc_data.currents.eval(last)?);
+    }
+
+    // Extend is the default, and extrapolates.
+    assert_eq!(
+        bfield(Extend)?.eval(beyond, theta)?,
+        nc_data.bfield_spline()?.eval(beyond, theta)?
+    );
+    assert_ne!(
+        currents(Extend)?.eval(beyond)?,
+        currents(Extend)?.eval(last)?
+    );
+
+    // Error reports the offending coordinate.
+    match bfield(Error)?.eval(beyond, theta) {
+        Err(NcError::OutOfDomain { name, value, span }) => {
+            assert_eq!((&*name, value, span), ("psi", beyond, (first, last)));
+        }
+        other => panic!("expected OutOfDomain, got {other:?}"),
+    }
+    assert!(matches!(
+        currents(Error)?.eval(first - 1.0),
+        Err(NcError::OutOfDomain { .. })
+    ));
+
+    // Clamp evaluates on the boundary.
+    assert_eq!(
+        bfield(Clamp)?.eval(beyond, theta)?,
+        bfield(Clamp)?.eval(last, theta)?
+    );
+    assert_eq!(
+        currents(Clamp)?.eval(first - 1.0)?,
+        currents(Clamp)?.eval(first)?
+    );
+
+    // Vacuum keeps the last surface's values and θ variation, without ψ derivatives.
+    let (b, db_dpsi, db_dtheta, d2b) = bfield(Vacuum)?.eval(beyond, theta)?;
+    let boundary = bfield(Vacuum)?.eval(last, theta)?;
+    assert_eq!(
+        (b, db_dpsi, db_dtheta, d2b),
+        (boundary.0, 0.0, boundary.2, 0.0)
+    );
+    assert!(db_dtheta.abs() > 0.0);
+    let (i, g, di, dg) = currents(Vacuum)?.eval(beyond)?;
+    let boundary = currents(Vacuum)?.eval(last)?;
+    assert_eq!((i, g, di, dg), (boundary.0, boundary.1, 0.0, 0.0));
+
+    // The `Currents`' own evaluator follows its policy too.
+    let own = CircularTokamak::default().to_nc_data()?.currents;
+    let own = own.with_domain(Error);
+    assert!(matches!(own.eval(beyond), Err(NcError::OutOfDomain { .. })));
+    let own = own.with_domain(Vacuum);
+    assert_eq!(own.eval(beyond)?, (i, g, di, dg));
+    assert_eq!(own.eval(inside)?, nc_data.currents.eval(inside)?);
+
+    // Errors name the equilibrium's own ψ variable.
+    let mut renamed = CircularTokamak::default().to_nc_data()?;
+    renamed.schema = NcSchema::psi_tor();
+    assert!(matches!(
+        renamed.bfield_spline()?.with_domain(Error).eval(beyond, theta),
+        Err(NcError::OutOfDomain { name, .. }) if &*name == "psi_tor"
+    ));
+    assert!(matches!(
+        renamed.currents_spline_with(&Cubic)?.with_domain(Error).eval(beyond),
+        Err(NcError::OutOfDomain { name, .. }) if &*name == "psi_tor"
+    ));
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_packed_variables() -> Result<(), NcError> {
+    let schema = NcSchema::new().with_b_field("b_packed");
+
+    let path = common::packed_netcdf_path(false).unwrap();
+    let packed = NcData::open_with(path.clone(), &schema)?;
+    let unpacked = NcData::open(path.clone())?;
+    assert!(
+        (&packed.bfield.b - &unpacked.bfield.b)
+            .iter()
+            .all(|d| d.abs() < 1e-12)
+    );
+    std::fs::remove_file(path).unwrap();
+
+    let path = common::packed_netcdf_path(true).unwrap();
+    assert!(matches!(
+        NcData::open_with(path.clone(), &schema),
+        Err(NcError::MaskedValues { count: 1, .. })
+    ));
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[test]
+#[cfg(feature = "netcdf")]
+fn test_nc_data_groups() -> Result<(), NcError> {
+    let path = common::grouped_netcdf_path().unwrap();
+
+    let shot1 = NcData::open_group(path.clone(), "shot1/equilibrium")?;
+    let shot2 = NcData::open_group(path.clone(), "/shot2/equilibrium/")?;
+    assert_eq!(
+        &shot2.bfield.b.slice(ndarray::s![1.., ..]),
+        &(&shot1.bfield.b.slice(ndarray::s![1.., ..]) * 2.0)
+    );
+    assert_eq!(shot1.metadata.shot, Some(1));
+    assert_eq!(shot2.metadata.shot, Some(2));
+    assert_eq!(shot2.metadata.code.as_deref(), Some("phony"));
+    assert!(shot1.profiles.is_none());
+
+    // Absolute paths reach outside the group.
+    let schema = NcSchema::default()
+        .with_group("shot1/equilibrium")
+        .with_q("/profiles/q");
+    let with_q = NcData::open_with(path.clone(), &schema)?;
+    let q = with_q.profiles.as_ref().unwrap().q.as_ref().unwrap();
+    assert_eq!(q.slice(ndarray::s![1..]), ndarray::array![1.1, 2.5]);
+
+    // Grouped equilibria are written back in the same layout.
+    let written = common::unique_temp_path("grouped_write");
+    with_q.write(&written)?;
+    let reread = NcData::open_with(written.clone(), &schema)?;
+    assert_eq!(reread.bfield.b, with_q.bfield.b);
+    assert_eq!(reread.profiles.unwrap().q, with_q.profiles.unwrap().q);
+    std::fs::remove_file(written).unwrap();
+
+    assert!(matches!(
+        NcData::open_group(path.clone(), "shot3"),
+        Err(NcError::GroupNotFound(_))
+    ));
+    assert!(matches!(
+        NcData::open(path.clone()),
+        Err(NcError::VariableNotFound(_))
+    ));
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}
+
+#[cfg(feature = "classic")]
+#[test]
+fn test_nc_data_classic() -> Result<(), NcError> {
+    use netcdf::Options;
+
+    for options in [Options::CLASSIC, Options::_64BIT_OFFSET] {
+        let path = common::classic_netcdf_path(options).unwrap();
+        let classic = NcData::open_classic(path.clone())?;
+        let nc_data = NcData::open(path.clone())?;
+
+        assert_eq!(classic.coords.psi, nc_data.coords.psi);
+        assert_eq!(classic.coords.theta, nc_data.coords.theta);
+        assert_eq!(classic.currents.g, nc_data.currents.g);
+        assert_eq!(classic.currents.i, nc_data.currents.i);
+        assert_eq!(classic.bfield.b, nc_data.bfield.b);
+        assert_eq!(classic.scalars.raxis, 1.65);
+        assert_eq!(classic.metadata, nc_data.metadata);
+        assert_eq!(classic.metadata.shot, Some(42));
+        std::fs::remove_file(path).unwrap();
+    }
+
+    // NetCDF-4 files need libnetcdf.
+    let path = common::phony_netcdf_path().unwrap();
+    assert!(matches!(
+        NcData::open_classic(path.clone()),
+        Err(NcError::ClassicFormat(_))
+    ));
+    std::fs::remove_file(path).unwrap();
+    Ok(())
+}